@@ -9,7 +9,7 @@ const gamePath = 'E:\\Games\\Overwatch\\Overwatch'
 const __filename = fileURLToPath(import.meta.url)
 const __dirname = path.dirname(__filename)
 const dataToolPath = path.join(__dirname, '../toolchain-release/DataTool.exe')
-const backgroundsFile = path.join(__dirname, '../src-tauri/src/backgrounds.rs')
+const backgroundsFile = path.join(__dirname, '../src-tauri/backgrounds.toml')
 
 // Function to run the DataTool.exe and capture its output
 function runDataTool(): Promise<string> {
@@ -33,12 +33,12 @@ function normalizeId(id: string): string {
   return id.replace(/^0+/, '') // Remove leading zeros
 }
 
-// Function to extract IDs from backgrounds.rs
+// Function to extract IDs from backgrounds.toml
 async function extractBackgroundIds(
   backgroundsFile: string
 ): Promise<string[]> {
   const content = await readFile(backgroundsFile, 'utf-8')
-  const idRegex = /^\s*id: "0x[0-9A-Fa-f]+",/gm
+  const idRegex = /^id = "0x[0-9A-Fa-f]+"/gm
   const matches = content.match(idRegex)
 
   if (!matches) {
@@ -85,7 +85,7 @@ try {
     mapData.map((data) => data.name)
   )
 
-  // Step 4: List extra IDs in DataTool output that are not in backgrounds.rs
+  // Step 4: List extra IDs in DataTool output that are not in backgrounds.toml
   const extraIds = mapData.filter(({ id }) => !backgroundIds.includes(id))
 
   if (extraIds.length > 0) {
@@ -93,10 +93,12 @@ try {
       .map(({ id, name }) => `${id} (${name})`)
       .join('\n')
     console.log(
-      `IDs in DataTool output but not in backgrounds.rs:\n${extraIdOutput}\n`
+      `IDs in DataTool output but not in backgrounds.toml:\n${extraIdOutput}\n`
     )
   } else {
-    console.log('All IDs from DataTool output are present in backgrounds.rs.\n')
+    console.log(
+      'All IDs from DataTool output are present in backgrounds.toml.\n'
+    )
   }
 
   // Step 5: Compare IDs and find IDs in backgroundIds but not in mapIds
@@ -104,10 +106,10 @@ try {
 
   if (missingIds.length > 0) {
     console.log(
-      `IDs in backgrounds.rs but not in DataTool output: ${missingIds.join(', ')}`
+      `IDs in backgrounds.toml but not in DataTool output: ${missingIds.join(', ')}`
     )
   } else {
-    console.log('All IDs from backgrounds.rs are present in DataTool output.')
+    console.log('All IDs from backgrounds.toml are present in DataTool output.')
   }
 } catch (error) {
   console.error(`Error: ${error}`)
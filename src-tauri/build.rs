@@ -1,3 +1,208 @@
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+static CATALOG_FILE: &str = "backgrounds.toml";
+static IMAGE_DIR: &str = "../public/backgrounds";
+/// Number of hex digits in a background id, e.g. `0x0800000000000E1F`.
+const ID_WIDTH: usize = 16;
+
+#[derive(serde::Deserialize)]
+struct Catalog {
+    background: Vec<Background>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Background {
+    id: String,
+    image: String,
+    name: String,
+    description: String,
+    season: Option<u32>,
+    release_date: String,
+    event: Option<String>,
+    #[serde(default)]
+    heroes: Vec<String>,
+    /// Names of `backgrounds::Tag` variants.
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default = "default_has_music")]
+    has_music: bool,
+    music_credit: Option<String>,
+    #[serde(default)]
+    status: Status,
+    since: Option<String>,
+    successor: Option<String>,
+}
+
+#[derive(serde::Deserialize, Default, PartialEq, Debug)]
+enum Status {
+    #[default]
+    Active,
+    Broken,
+    Removed,
+}
+
+fn default_has_music() -> bool {
+    true
+}
+
 fn main() {
+    println!("cargo:rerun-if-changed={}", CATALOG_FILE);
+    println!("cargo:rerun-if-changed={}", IMAGE_DIR);
+
+    let catalog = fs::read_to_string(CATALOG_FILE)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {}", CATALOG_FILE, e));
+    let catalog: Catalog = toml::from_str(&catalog)
+        .unwrap_or_else(|e| panic!("Failed to parse {}: {}", CATALOG_FILE, e));
+
+    let errors = validate_catalog(&catalog.background);
+    if !errors.is_empty() {
+        panic!(
+            "Invalid background catalog in {}:\n  {}",
+            CATALOG_FILE,
+            errors.join("\n  ")
+        );
+    }
+
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is not set");
+    fs::write(
+        Path::new(&out_dir).join("backgrounds.rs"),
+        generate_catalog(&catalog.background),
+    )
+    .expect("Failed to write the generated background catalog");
+
     tauri_build::build()
 }
+
+/// Check for duplicate ids, malformed ids and missing images.
+fn validate_catalog(backgrounds: &[Background]) -> Vec<String> {
+    let mut errors = vec![];
+    let mut ids = HashSet::new();
+
+    for background in backgrounds {
+        let is_well_formed = background.id.strip_prefix("0x").is_some_and(|digits| {
+            digits.len() == ID_WIDTH && digits.chars().all(|char| char.is_ascii_hexdigit())
+        });
+        if !is_well_formed {
+            errors.push(format!(
+                "\"{}\" ({}) is not 0x followed by {} hexadecimal digits",
+                background.id, background.name, ID_WIDTH
+            ));
+        }
+
+        if !ids.insert(background.id.to_ascii_uppercase()) {
+            errors.push(format!(
+                "\"{}\" ({}) is used by more than one background",
+                background.id, background.name
+            ));
+        }
+
+        if !is_valid_date(&background.release_date) {
+            errors.push(format!(
+                "\"{}\" ({}) has release date \"{}\", expected YYYY-MM-DD",
+                background.id, background.name, background.release_date
+            ));
+        }
+
+        if let Some(tag) = background.tags.iter().find(|tag| {
+            !tag.starts_with(|char: char| char.is_ascii_uppercase())
+                || !tag.chars().all(|char| char.is_ascii_alphanumeric())
+        }) {
+            errors.push(format!(
+                "\"{}\" ({}) has tag \"{}\", expected the name of a Tag variant",
+                background.id, background.name, tag
+            ));
+        }
+
+        if !background.has_music && background.music_credit.is_some() {
+            errors.push(format!(
+                "\"{}\" ({}) has a music credit but no music",
+                background.id, background.name
+            ));
+        }
+
+        if background.status == Status::Active && background.since.is_some() {
+            errors.push(format!(
+                "\"{}\" ({}) is active but has a retirement date",
+                background.id, background.name
+            ));
+        }
+
+        if let Some(successor) = &background.successor {
+            match backgrounds.iter().find(|other| other.id == *successor) {
+                Some(other) if other.status == Status::Active && other.id != background.id => {}
+                Some(_) => errors.push(format!(
+                    "\"{}\" ({}) has successor \"{}\", which is not an active background",
+                    background.id, background.name, successor
+                )),
+                None => errors.push(format!(
+                    "\"{}\" ({}) has successor \"{}\", which is not in the catalog",
+                    background.id, background.name, successor
+                )),
+            }
+        }
+
+        if !Path::new(IMAGE_DIR).join(&background.image).is_file() {
+            errors.push(format!(
+                "\"{}\" ({}) has no image at {}/{}",
+                background.id, background.name, IMAGE_DIR, background.image
+            ));
+        }
+    }
+
+    errors
+}
+
+fn is_valid_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = parts[..] else {
+        return false;
+    };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return false;
+    }
+
+    matches!(
+        (year.parse::<u32>(), month.parse::<u32>(), day.parse::<u32>()),
+        (Ok(_), Ok(1..=12), Ok(1..=31))
+    )
+}
+
+/// Generate the `BACKGROUNDS` array included by `src/backgrounds.rs`.
+fn generate_catalog(backgrounds: &[Background]) -> String {
+    let mut code = String::from("const BACKGROUNDS: &[Background] = &[\n");
+    for background in backgrounds {
+        let tags: Vec<String> = background
+            .tags
+            .iter()
+            .map(|tag| format!("Tag::{}", tag))
+            .collect();
+        let status = match background.status {
+            Status::Active => "Status::Active".to_string(),
+            Status::Broken => format!("Status::Broken({:?})", background.since),
+            Status::Removed => format!("Status::Removed({:?})", background.since),
+        };
+        code.push_str(&format!(
+            "    Background {{\n        id: {:?},\n        image: {:?},\n        name: {:?},\n        description: {:?},\n        season: {:?},\n        release_date: {:?},\n        event: {:?},\n        heroes: &{:?},\n        tags: &[{}],\n        has_music: {},\n        music_credit: {:?},\n        status: {},\n        successor: {:?},\n    }},\n",
+            background.id,
+            background.image,
+            background.name,
+            background.description,
+            background.season,
+            background.release_date,
+            background.event,
+            background.heroes,
+            tags.join(", "),
+            background.has_music,
+            background.music_credit,
+            status,
+            background.successor
+        ));
+    }
+    code.push_str("];\n");
+
+    code
+}
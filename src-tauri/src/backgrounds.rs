@@ -1,4 +1,64 @@
-use serde::Serialize;
+use crate::config::{
+    BackgroundError, BackgroundErrorKey, Collection, CustomBackground, RetiredBackground,
+    RotationPool,
+};
+use crate::helpers::Error;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Tag {
+    Animated,
+    #[serde(rename = "Animated Art")]
+    AnimatedArt,
+    Art,
+    Collaboration,
+    #[serde(rename = "Mythic Weapon")]
+    MythicWeapon,
+    #[serde(rename = "Overwatch League")]
+    OverwatchLeague,
+    #[serde(rename = "World Cup")]
+    WorldCup,
+    #[serde(rename = "Overwatch Champions Series")]
+    ChampionsSeries,
+    Charity,
+    #[serde(rename = "No Hero")]
+    NoHero,
+    #[serde(rename = "Crowd Sounds")]
+    CrowdSounds,
+    #[serde(rename = "Overwatch 1.0 Patch")]
+    ClassicPatch,
+}
+
+impl Tag {
+    /// Get the name shown in the app, matching the serialized name.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Tag::Animated => "Animated",
+            Tag::AnimatedArt => "Animated Art",
+            Tag::Art => "Art",
+            Tag::Collaboration => "Collaboration",
+            Tag::MythicWeapon => "Mythic Weapon",
+            Tag::OverwatchLeague => "Overwatch League",
+            Tag::WorldCup => "World Cup",
+            Tag::ChampionsSeries => "Overwatch Champions Series",
+            Tag::Charity => "Charity",
+            Tag::NoHero => "No Hero",
+            Tag::CrowdSounds => "Crowd Sounds",
+            Tag::ClassicPatch => "Overwatch 1.0 Patch",
+        }
+    }
+}
+
+/// Whether a background still shows in the game.
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(tag = "kind", content = "since")]
+pub enum Status {
+    Active,
+    /// The id no longer shows the background, e.g. since a mid-season patch.
+    Broken(Option<&'static str>),
+    /// The background was removed from the game, e.g. in a season.
+    Removed(Option<&'static str>),
+}
 
 #[derive(Serialize)]
 pub struct Background {
@@ -6,484 +66,682 @@ pub struct Background {
     pub image: &'static str,
     pub name: &'static str,
     pub description: &'static str,
-    pub tags: &'static [&'static str],
+    pub season: Option<u32>,
+    /// Start of the season or event the background shipped with, as `YYYY-MM-DD`.
+    pub release_date: &'static str,
+    pub event: Option<&'static str>,
+    pub heroes: &'static [&'static str],
+    pub tags: &'static [Tag],
+    pub has_music: bool,
+    pub music_credit: Option<&'static str>,
+    pub status: Status,
+    /// The background to migrate to once this one is retired.
+    pub successor: Option<&'static str>,
+}
+
+impl Background {
+    pub fn is_retired(&self) -> bool {
+        self.status != Status::Active
+    }
+
+    /// Explain why the background is retired, or `None` if it is active.
+    pub fn get_retirement_reason(&self) -> Option<String> {
+        match self.status {
+            Status::Active => None,
+            Status::Broken(Some(since)) => Some(format!(
+                "[[{}]] stopped working with the {}",
+                self.name, since
+            )),
+            Status::Broken(None) => Some(format!("[[{}]] no longer works in Overwatch", self.name)),
+            Status::Removed(Some(since)) => {
+                Some(format!("[[{}]] was removed in {}", self.name, since))
+            }
+            Status::Removed(None) => Some(format!("[[{}]] was removed from Overwatch", self.name)),
+        }
+    }
+}
+
+// Generated by build.rs from backgrounds.toml
+include!(concat!(env!("OUT_DIR"), "/backgrounds.rs"));
+
+pub fn find_background_by_id(id: &str) -> Option<&'static Background> {
+    BACKGROUNDS
+        .iter()
+        .find(|&bg| bg.id.eq_ignore_ascii_case(id))
+}
+
+/// Number of hex digits in a background id, e.g. `0x0800000000000E1F`.
+const ID_WIDTH: usize = 16;
+
+/// A background as listed in the app, either from the catalog or added by the user.
+#[derive(Serialize)]
+pub struct ListedBackground<'a> {
+    pub id: &'a str,
+    pub image: &'a str,
+    pub name: &'a str,
+    pub description: &'a str,
+    pub season: Option<u32>,
+    pub release_date: Option<&'a str>,
+    pub event: Option<&'a str>,
+    pub heroes: &'a [&'a str],
+    pub tags: &'a [Tag],
+    /// Unknown for custom backgrounds.
+    pub has_music: Option<bool>,
+    pub music_credit: Option<&'a str>,
+    pub status: Status,
+    pub successor: Option<&'a str>,
+    /// Whether the background was added since the user last looked at new backgrounds.
     pub new: bool,
+    pub custom: bool,
+}
+
+impl<'a> From<&'a Background> for ListedBackground<'a> {
+    fn from(background: &'a Background) -> Self {
+        ListedBackground {
+            id: background.id,
+            image: background.image,
+            name: background.name,
+            description: background.description,
+            season: background.season,
+            release_date: Some(background.release_date),
+            event: background.event,
+            heroes: background.heroes,
+            tags: background.tags,
+            has_music: Some(background.has_music),
+            music_credit: background.music_credit,
+            status: background.status,
+            successor: background.successor,
+            new: false,
+            custom: false,
+        }
+    }
+}
+
+impl<'a> From<&'a CustomBackground> for ListedBackground<'a> {
+    fn from(background: &'a CustomBackground) -> Self {
+        ListedBackground {
+            id: &background.id,
+            image: background.image.as_deref().unwrap_or_default(),
+            name: &background.name,
+            description: "Custom Background",
+            season: None,
+            release_date: None,
+            event: None,
+            heroes: &[],
+            tags: &[],
+            has_music: None,
+            music_credit: None,
+            status: Status::Active,
+            successor: None,
+            new: false,
+            custom: true,
+        }
+    }
+}
+
+impl<'a> ListedBackground<'a> {
+    /// List a catalog background, marking it new if the user has not seen its id.
+    fn from_catalog(background: &'a Background, seen: &[String]) -> Self {
+        ListedBackground {
+            new: is_new(background, seen),
+            ..ListedBackground::from(background)
+        }
+    }
 }
 
-const BACKGROUNDS: &[Background] = &[
-    Background {
-        id: "0x0800000000000864",
-        image: "overwatch_league.jpg",
-        name: "Overwatch League",
-        description: "Overwatch League Promo",
-        tags: &["Silent"],
-        new: false,
-    },
-    Background {
-        id: "0x0800000000000E77",
-        image: "heroes.jpg",
-        name: "Heroes",
-        description: "2022 Alpha Test",
-        tags: &["Animated"],
-        new: false,
-    },
-    Background {
-        id: "0x0800000000000D6C",
-        image: "sojourn.jpg",
-        name: "Sojourn",
-        description: "2022 PvP Beta",
-        tags: &[],
-        new: false,
-    },
-    // Background {
-    //     id: "0x0800000000000EF3",
-    //     image: "kiriko.jpg",
-    //     name: "Kiriko",
-    //     description: "Kiriko Release (Season 1)",
-    //     tags: vec!["Song: BOW by MFS"],
-    // },
-    Background {
-        id: "0x0800000000000EFB",
-        image: "zero_hour.jpg",
-        name: "Zero Hour",
-        description: "Overwatch 2 Launch",
-        tags: &[],
-        new: false,
-    },
-    // Broken after Season 13 mid-season patch
-    // Background {
-    //     id: "0x0800000000000F11",
-    //     image: "cyber_demon_genji_green.jpg",
-    //     name: "Cyber Demon Genji (Green)",
-    //     description: "Season 1 Mythic Skin",
-    //     tags: &["Animated"],
-    // },
-    // Background {
-    //     id: "0x0800000000000EFA",
-    //     image: "cyber_demon_genji_red.jpg",
-    //     name: "Cyber Demon Genji (Red)",
-    //     description: "Season 1 Mythic Skin",
-    //     tags: &["Animated"],
-    // },
-    // Background {
-    //     id: "0x0800000000000F12",
-    //     image: "cyber_demon_genji_pink.jpg",
-    //     name: "Cyber Demon Genji (Pink)",
-    //     description: "Season 1 Mythic Skin",
-    //     tags: &["Animated"],
-    // },
-    // Background {
-    //     id: "0x0800000000000F8F",
-    //     image: "ramattra.jpg",
-    //     name: "Ramattra",
-    //     description: "Ramattra Release (Season 2)",
-    //     tags: vec![],
-    // },
-    // Background {
-    //     id: "0x0800000000000f31",
-    //     image: "shambali_monastery.jpg",
-    //     name: "Shambali Monastery",
-    //     description: "Shambali Monastery Release (Season 2)",
-    //     tags: vec!["No Hero"],
-    // },
-    // Background {
-    //     id: "0x0800000000000F4A",
-    //     image: "zeus_junker_queen.jpg",
-    //     name: "Zeus Junker Queen",
-    //     description: "Season 2 Mythic Skin",
-    //     tags: vec![],
-    // },
-    Background {
-        id: "0x0800000000000D77",
-        image: "winter_wonderland_2022.jpg",
-        name: "Festive Junkrat & Roadhog",
-        description: "Winter Wonderland 2022 (Season 2)",
-        tags: &[],
-        new: false,
-    },
-    Background {
-        id: "0x0800000000000DAD",
-        image: "mei_lunar_2023.jpg",
-        name: "Festive Mei",
-        description: "Lunar New Year 2023 (Season 2)",
-        tags: &[],
-        new: false,
-    },
-    Background {
-        id: "0x0800000000000710",
-        image: "dva_lunar_2023.jpg",
-        name: "Palanquin D.Va",
-        description: "Lunar New Year 2023 (Season 2)",
-        tags: &[],
-        new: false,
-    },
-    // Background {
-    //     id: "0x080000000000103D",
-    //     image: "antarctic_peninsula.jpg",
-    //     name: "Antarctic Peninsula",
-    //     description: "Antarctic Peninsula Release (Season 3)",
-    //     tags: vec!["No Hero"],
-    // },
-    // Background {
-    //     id: "0x0800000000001003",
-    //     image: "kiriko_amaterasu.jpg",
-    //     name: "Amaterasu Kiriko",
-    //     description: "Season 3 Mythic Skin",
-    //     tags: vec![],
-    // },
-    // Background {
-    //     id: "0x0800000000001045",
-    //     image: "kiriko_amaterasu_art.jpg",
-    //     name: "Amaterasu Kiriko Variations",
-    //     description: "Season 3 Mythic Skin",
-    //     tags: vec!["Art"],
-    // },
-    Background {
-        id: "0x0800000000000B6B",
-        image: "cupid_hanzo.jpg",
-        name: "Cupid Hanzo",
-        description: "Ultimate Valentine 2023 (Season 3)",
-        tags: &["Animated"],
-        new: false,
-    },
-    // Background {
-    //     id: "0x0800000000000EED",
-    //     image: "one_punch_man_doomfist.jpg",
-    //     name: "Saitama Doomfist",
-    //     description: "One Punch Man Event (Season 3)",
-    //     tags: vec!["Collaboration", "No Music"],
-    // },
-    // Background {
-    //     id: "0x0800000000001060",
-    //     image: "lifeweaver.jpg",
-    //     name: "Lifeweaver",
-    //     description: "Lifeweaver Release (Season 4)",
-    //     tags: vec![],
-    // },
-    Background {
-        id: "0x0800000000001032",
-        image: "galactic_emperor_sigma.jpg",
-        name: "Galactic Emperor Sigma",
-        description: "Season 4 Mythic Skin",
-        tags: &[],
-        new: false,
-    },
-    // Background {
-    //     id: "0x0800000000001132",
-    //     image: "starwatch_art.jpg",
-    //     name: "Starwatch",
-    //     description: "Starwatch Event (Season 4)",
-    //     tags: vec!["Art"],
-    // },
-    Background {
-        id: "0x0800000000001026",
-        image: "zero_hour_owl.jpg",
-        name: "Overwatch League Zero Hour",
-        description: "Overwatch League Promo (Season 4)",
-        tags: &["Overwatch League"],
-        new: false,
-    },
-    // Background {
-    //     id: "0x0800000000001133",
-    //     image: "questwatch_art.jpg",
-    //     name: "Questwatch",
-    //     description: "Questwatch Event (Season 5)",
-    //     tags: vec!["Art"],
-    // },
-    Background {
-        id: "0x0800000000000BCE",
-        image: "summer_games_2023.jpg",
-        name: "Tropical Doomfist",
-        description: "Summer Games 2023 (Season 5)",
-        tags: &[],
-        new: false,
-    },
-    // Background {
-    //     id: "0x080000000000112B",
-    //     image: "illari.jpg",
-    //     name: "Illari",
-    //     description: "Illari Release (Season 6)",
-    //     tags: vec![],
-    // },
-    // Background {
-    //     id: "0x08000000000010F2",
-    //     image: "ana_a_7000_wargod.jpg",
-    //     name: "A-7000 Wargod Ana",
-    //     description: "Season 6 Mythic Skin",
-    //     tags: vec![],
-    // },
-    Background {
-        id: "0x0800000000001104",
-        image: "gothenburg_mothership.jpg",
-        name: "Gothenburg Mothership",
-        description: "Invasion PvE Event (Season 6)",
-        tags: &["No Music"],
-        new: false,
-    },
-    // Background {
-    //     id: "0x0800000000001150",
-    //     image: "onryo_hanzo.jpg",
-    //     name: "Onryo Hanzo",
-    //     description: "Season 7 Mythic Skin",
-    //     tags: vec![],
-    // },
-    // Background {
-    //     id: "0x08000000000010AC",
-    //     image: "onryo_hanzo_art.jpg",
-    //     name: "Onryo Hanzo Art",
-    //     description: "Season 7 Mythic Skin",
-    //     tags: vec!["Art"],
-    // },
-    // Background {
-    //     id: "0x080000000000115C",
-    //     image: "lilith_moira.jpg",
-    //     name: "Lilith Moira",
-    //     description: "Halloween Terror 2023 (Season 7)",
-    //     tags: vec![],
-    // },
-    Background {
-        id: "0x0800000000000817",
-        image: "overwatch_world_cup.jpg",
-        name: "Overwatch World Cup",
-        description: "Overwatch World Cup 2023 (Season 7)",
-        tags: &["World Cup"],
-        new: false,
-    },
-    // Background {
-    //     id: "0x0800000000001173",
-    //     image: "le_sserafim_collab.jpg",
-    //     name: "LE SSERAFIM",
-    //     description: "LE SSERAFIM Event (Season 7)",
-    //     tags: vec![
-    //         "Collaboration",
-    //         "Song: Perfect Night by LE SSERAFIM",
-    //     ],
-    // },
-    // Background {
-    //     id: "0x080000000000118a",
-    //     image: "mauga.jpg",
-    //     name: "Mauga",
-    //     description: "Mauga Release (Season 8)",
-    //     tags: vec![],
-    // },
-    // Background {
-    //     id: "0x0800000000001197",
-    //     image: "grand_beast_orisa.jpg",
-    //     name: "Grand Beast Orisa",
-    //     description: "Season 8 Mythic Skin",
-    //     tags: vec![],
-    // },
-    // Background {
-    //     id: "0x08000000000011b4",
-    //     image: "winter_wonderland_2023.jpg",
-    //     name: "Festive Mercy, B.O.B. and Genji",
-    //     description: "Winter Wonderland 2023 (Season 8)",
-    //     tags: vec!["Art"],
-    // },
-    Background {
-        id: "0x0800000000001200",
-        image: "ancient_caller_moira.jpg",
-        name: "Ancient Caller Moira",
-        description: "Season 9 Mythic Skin",
-        tags: &["Art"],
-        new: false,
-    },
-    Background {
-        id: "0x080000000000121A",
-        image: "venture.jpg",
-        name: "Venture",
-        description: "Venture Release (Season 10)",
-        tags: &["Art"],
-        new: false,
-    },
-    Background {
-        id: "0x080000000000121E",
-        image: "mirrorwatch.jpg",
-        name: "Mirrorwatch",
-        description: "Mirrorwatch Event (Season 10)",
-        tags: &[],
-        new: false,
-    },
-    Background {
-        id: "0x08000000000010AC",
-        image: "mirrorwatch_art.jpg",
-        name: "Mirrorwatch Art",
-        description: "Mirrorwatch Event (Season 10)",
-        tags: &["Art"],
-        new: false,
-    },
-    Background {
-        id: "0x0800000000001219",
-        image: "porsche.jpg",
-        name: "Porsche D.Va",
-        description: "Porsche Event (Season 10)",
-        tags: &["Collaboration", "Art"],
-        new: false,
-    },
-    // Removed in Season 12, before I could even push to main :(
-    // Background {
-    //     id: "0x080000000000123F",
-    //     image: "calamity_empress_ashe.jpg",
-    //     name: "Calamity Empress Ashe",
-    //     description: "Season 11 Mythic Skin",
-    //     tags: &["Art"],
-    // },
-    Background {
-        id: "0x08000000000008BA",
-        image: "rose_gold_mercy.jpg",
-        name: "Rose Gold Mercy",
-        description: "Breast Cancer Charity Event (Season 11)",
-        tags: &["Charity"],
-        new: false,
-    },
-    // Removed in Season 12, before I could even push to main :(
-    // Background {
-    //     id: "0x0800000000001243",
-    //     image: "transformers.jpg",
-    //     name: "Optimus Prime Reinhardt",
-    //     description: "Transformers Event (Season 11)",
-    //     tags: &["Collaboration", "Art"],
-    // },
-    Background {
-        id: "0x0800000000001249",
-        image: "summer_games_2024.jpg",
-        name: "Lifeguard Lifeweaver & Kiriko",
-        description: "Summer Games 2024 (Season 11)",
-        tags: &[],
-        new: false,
-    },
-    Background {
-        id: "0x080000000000124A",
-        image: "reinhardt_bound_demon_mythic.jpg",
-        name: "Reinhardt Bound Demon",
-        description: "Reinhardt Mythic Weapon (Season 11)",
-        tags: &["Mythic Weapon"],
-        new: false,
-    },
-    // Background {
-    //     id: "0x0800000000001276",
-    //     image: "juno.jpg",
-    //     name: "Juno",
-    //     description: "Juno Release (Season 12)",
-    //     tags: &["Art"],
-    // },
-    Background {
-        id: "0x0800000000001277",
-        image: "anubis_reaper.jpg",
-        name: "Anubis Reaper",
-        description: "Season 12 Mythic Skin",
-        tags: &["Art"],
-        new: false,
-    },
-    Background {
-        id: "0x0800000000001293",
-        image: "world_of_warcraft.jpg",
-        name: "Sylvanas Widowmaker",
-        description: "World of Warcraft Event (Season 12)",
-        tags: &["Collaboration", "Art"],
-        new: false,
-    },
-    Background {
-        id: "0x0800000000001289",
-        image: "ana_midnight_sun_mythic.jpg",
-        name: "Ana Midnight Sun",
-        description: "Ana Mythic Weapon (Season 12)",
-        tags: &["Mythic Weapon"],
-        new: false,
-    },
-    Background {
-        id: "0x08000000000012A5",
-        image: "spellbinder_widowmaker.jpg",
-        name: "Spellbinder Widowmaker",
-        description: "Season 13 Mythic Skin",
-        tags: &["Art"],
-        new: false,
-    },
-    Background {
-        id: "0x08000000000012A3",
-        image: "owcs_finals_2024.jpg",
-        name: "OWCS Finals 2024",
-        description: "Overwatch Championship Series Finals 2024",
-        tags: &["Overwatch Champions Series", "Art", "Crowd Sounds"],
-        new: false,
-    },
-    // Background {
-    //     id: "0x08000000000012A4",
-    //     image: "my_hero_academia.jpg",
-    //     name: "My Hero Academia",
-    //     description: "My Hero Academia Event (Season 13)",
-    //     tags: &["Collaboration", "Art"],
-    // },
-    Background {
-        id: "0x08000000000012C2",
-        image: "overwatch_classic.jpg",
-        name: "Overwatch Classic",
-        description: "Overwatch Classic Event (Season 13)",
-        tags: &["Overwatch 1.0 Patch", "Original Theme Song"],
-        new: false,
-    },
-    Background {
-        id: "0x08000000000012BE",
-        image: "soldier_76_deliverance.jpg",
-        name: "Soldier: 76 Deliverance",
-        description: "Soldier: 76 Mythic Weapon (Season 13)",
-        tags: &["Mythic Weapon"],
-        new: false,
-    },
-    Background {
-        id: "0x08000000000012C6",
-        image: "hazard.jpg",
-        name: "Hazard",
-        description: "Hazard Release (Season 14)",
-        tags: &["Art"],
-        new: false,
-    },
-    Background {
-        id: "0x08000000000012C7",
-        image: "thor_reinhardt.jpg",
-        name: "Thor Reinhardt",
-        description: "Season 14 Mythic Skin",
-        tags: &["Animated Art"],
-        new: false,
-    },
-    Background {
-        id: "0x08000000000012EA",
-        image: "welcome_home_china.jpg",
-        name: "Welcome Home, China",
-        description: "China Re-release with NetEase",
-        tags: &["Art"],
-        new: false,
-    },
-    Background {
-        id: "0x08000000000012CA",
-        image: "avatar_the_last_airbender.jpg",
-        name: "Avatar: The Last Airbender",
-        description: "Avatar: The Last Airbender Event (Season 14)",
-        tags: &["Collaboration", "Animated Art"],
-        new: false,
-    },
-    Background {
-        id: "0x08000000000012CB",
-        image: "ashe_lead_rose.jpg",
-        name: "Ashe Lead Rose",
-        description: "Ashe Mythic Weapon (Season 14)",
-        tags: &["Mythic Weapon"],
-        new: true,
-    },
-    Background {
-        id: "0x0800000000001300",
-        image: "celestial_skins.jpg",
-        name: "Celestial Skins",
-        description: "Celestial Skin Bundle (Season 14)",
-        tags: &[],
-        new: true,
-    },
-];
-
-pub fn get_backgrounds() -> &'static [Background] {
+/// Get the ids of every background in the catalog.
+pub fn get_catalog_ids() -> Vec<String> {
     BACKGROUNDS
+        .iter()
+        .map(|background| background.id.to_string())
+        .collect()
 }
 
-pub fn find_background_by_id(id: &str) -> Option<&'static Background> {
-    BACKGROUNDS.iter().find(|&bg| bg.id == id)
+/// Backgrounds that were flagged as new by hand before seen ids were recorded per user.
+const LEGACY_NEW_IDS: &[&str] = &["0x08000000000012CB", "0x0800000000001300"];
+
+/// Get the ids to record as seen for a config without any. A fresh install has seen the
+/// whole catalog, while an upgrading user has not seen the backgrounds that were still
+/// flagged as new.
+pub fn get_initial_seen_ids(is_upgrade: bool) -> Vec<String> {
+    BACKGROUNDS
+        .iter()
+        .filter(|background| !is_upgrade || !LEGACY_NEW_IDS.contains(&background.id))
+        .map(|background| background.id.to_string())
+        .collect()
+}
+
+/// Check if an active background was added to the catalog since the user last saw it.
+fn is_new(background: &Background, seen: &[String]) -> bool {
+    !background.is_retired() && !seen.iter().any(|id| id.eq_ignore_ascii_case(background.id))
+}
+
+/// Get the active backgrounds in the catalog followed by the custom backgrounds.
+pub fn list_backgrounds<'a>(
+    custom: &'a [CustomBackground],
+    seen: &[String],
+) -> Vec<ListedBackground<'a>> {
+    BACKGROUNDS
+        .iter()
+        .filter(|background| !background.is_retired())
+        .map(|background| ListedBackground::from_catalog(background, seen))
+        .chain(custom.iter().map(ListedBackground::from))
+        .collect()
+}
+
+/// Get the active backgrounds the user has not seen yet, newest first.
+pub fn list_new_backgrounds(seen: &[String]) -> Vec<ListedBackground<'static>> {
+    let mut results: Vec<&Background> = BACKGROUNDS
+        .iter()
+        .filter(|background| is_new(background, seen))
+        .collect();
+    results.sort_by(|a, b| b.release_date.cmp(a.release_date));
+
+    results
+        .into_iter()
+        .map(|background| ListedBackground::from_catalog(background, seen))
+        .collect()
+}
+
+/// A background saved by the user, which may have since left the catalog.
+#[derive(Serialize)]
+pub struct SavedBackground<'a> {
+    pub id: &'a str,
+    /// `None` if the id is neither in the catalog nor a custom background.
+    pub background: Option<ListedBackground<'a>>,
+}
+
+/// A collection with its backgrounds resolved.
+#[derive(Serialize)]
+pub struct ListedCollection<'a> {
+    pub name: &'a str,
+    pub backgrounds: Vec<SavedBackground<'a>>,
+}
+
+/// Resolve saved ids in order. Retired backgrounds are listed with their status so they
+/// can be replaced, and ids removed from the catalog are kept without a background.
+pub fn list_saved_backgrounds<'a>(
+    ids: &'a [String],
+    custom: &'a [CustomBackground],
+    seen: &[String],
+) -> Vec<SavedBackground<'a>> {
+    ids.iter()
+        .map(|id| SavedBackground {
+            id,
+            background: find_background_by_id(id)
+                .map(|background| ListedBackground::from_catalog(background, seen))
+                .or_else(|| {
+                    custom
+                        .iter()
+                        .find(|background| background.id.eq_ignore_ascii_case(id))
+                        .map(ListedBackground::from)
+                }),
+        })
+        .collect()
+}
+
+/// Resolve the backgrounds of every collection.
+pub fn list_collections<'a>(
+    collections: &'a [Collection],
+    custom: &'a [CustomBackground],
+    seen: &[String],
+) -> Vec<ListedCollection<'a>> {
+    collections
+        .iter()
+        .map(|collection| ListedCollection {
+            name: &collection.name,
+            backgrounds: list_saved_backgrounds(&collection.backgrounds, custom, seen),
+        })
+        .collect()
+}
+
+/// Filters for searching backgrounds. Empty fields match every background.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct BackgroundFilter {
+    pub season: Option<u32>,
+    pub event: Option<String>,
+    pub hero: Option<String>,
+    /// Backgrounds must have every tag.
+    pub tags: Vec<Tag>,
+    pub has_music: Option<bool>,
+    /// Also match broken and removed backgrounds.
+    pub include_retired: bool,
+}
+
+impl BackgroundFilter {
+    fn is_empty(&self) -> bool {
+        self.season.is_none()
+            && self.event.is_none()
+            && self.hero.is_none()
+            && self.tags.is_empty()
+            && self.has_music.is_none()
+    }
+
+    fn matches(&self, background: &Background) -> bool {
+        (self.include_retired || !background.is_retired())
+            && self
+                .season
+                .is_none_or(|season| background.season == Some(season))
+            && self.event.as_ref().is_none_or(|event| {
+                background
+                    .event
+                    .is_some_and(|other| other.eq_ignore_ascii_case(event))
+            })
+            && self.hero.as_ref().is_none_or(|hero| {
+                background
+                    .heroes
+                    .iter()
+                    .any(|other| other.eq_ignore_ascii_case(hero))
+            })
+            && self.tags.iter().all(|tag| background.tags.contains(tag))
+            && self
+                .has_music
+                .is_none_or(|has_music| background.has_music == has_music)
+    }
+}
+
+/// Search backgrounds by text and filters, newest first.
+///
+/// Custom backgrounds have no metadata, so they are only matched by name when no filters
+/// are set and are listed after the catalog.
+pub fn search_backgrounds<'a>(
+    query: &str,
+    filter: &BackgroundFilter,
+    custom: &'a [CustomBackground],
+    seen: &[String],
+) -> Vec<ListedBackground<'a>> {
+    let mut results: Vec<&Background> = BACKGROUNDS
+        .iter()
+        .filter(|background| filter.matches(background))
+        .filter(|background| {
+            let text = [
+                background.name,
+                background.description,
+                background.event.unwrap_or_default(),
+                background.music_credit.unwrap_or_default(),
+            ]
+            .into_iter()
+            .chain(background.heroes.iter().copied())
+            .chain(background.tags.iter().map(Tag::label))
+            .collect::<Vec<_>>()
+            .join(" ");
+            fuzzy_matches(query, &text)
+        })
+        .collect();
+    results.sort_by(|a, b| b.release_date.cmp(a.release_date));
+
+    let custom = custom
+        .iter()
+        .filter(|background| filter.is_empty() && fuzzy_matches(query, &background.name));
+
+    results
+        .into_iter()
+        .map(|background| ListedBackground::from_catalog(background, seen))
+        .chain(custom.map(ListedBackground::from))
+        .collect()
+}
+
+/// Get the ids a rotation can pick from. Ids chosen by the user that are no longer
+/// available are skipped.
+pub fn get_rotation_pool(pool: &RotationPool, custom: &[CustomBackground]) -> Vec<String> {
+    match pool {
+        RotationPool::Catalog => BACKGROUNDS
+            .iter()
+            .filter(|background| !background.is_retired())
+            .map(|background| background.id.to_string())
+            .collect(),
+        RotationPool::Tags(tags) => {
+            let filter = BackgroundFilter {
+                tags: tags.clone(),
+                ..Default::default()
+            };
+            BACKGROUNDS
+                .iter()
+                .filter(|background| filter.matches(background))
+                .map(|background| background.id.to_string())
+                .collect()
+        }
+        RotationPool::Backgrounds(ids) => ids
+            .iter()
+            .filter(|id| validate_background_id(id, custom).is_ok())
+            .cloned()
+            .collect(),
+    }
+}
+
+/// Get the pool ids a rotation can pick next, skipping ids already used this round and ids
+/// set on any target. Once every id was used, `used` is cleared to start a new round.
+pub fn pick_rotation_candidates(
+    pool: &[String],
+    used: &mut Vec<String>,
+    current: &[String],
+) -> Vec<String> {
+    let get_candidates = |used: &[String]| -> Vec<String> {
+        pool.iter()
+            .filter(|id| !used.contains(id) && !current.contains(id))
+            .cloned()
+            .collect()
+    };
+    let candidates = get_candidates(used);
+    if !candidates.is_empty() {
+        return candidates;
+    }
+
+    used.clear();
+    get_candidates(&[])
+}
+
+/// Check that every word of the query is part of a word in the text, or close to one
+/// allowing for typos in longer words. Numbers such as years must match exactly.
+fn fuzzy_matches(query: &str, text: &str) -> bool {
+    let words = get_words(text);
+    get_words(query).iter().all(|term| {
+        let max_typos = match term.chars().count() {
+            _ if term.chars().any(char::is_numeric) => 0,
+            0..=3 => 0,
+            4..=7 => 1,
+            _ => 2,
+        };
+        words
+            .iter()
+            .any(|word| word.contains(term.as_str()) || edit_distance(term, word) <= max_typos)
+    })
+}
+
+/// Split text into lowercase words, ignoring punctuation such as in "D.Va".
+fn get_words(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|char| char.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Get the number of single character edits between two words.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Check if an id is in the catalog or one of the custom backgrounds, ignoring case.
+pub fn is_known_id(id: &str, custom: &[CustomBackground]) -> bool {
+    find_background_by_id(id).is_some()
+        || custom
+            .iter()
+            .any(|background| background.id.eq_ignore_ascii_case(id))
+}
+
+/// Write the hex digits of an id in uppercase, like the ids in the catalog.
+pub fn normalize_id(id: &str) -> String {
+    match id.strip_prefix("0x") {
+        Some(digits) => format!("0x{}", digits.to_ascii_uppercase()),
+        None => id.to_string(),
+    }
+}
+
+/// Check that a background id is `0x` followed by the expected number of hex digits, and get
+/// it normalized.
+pub fn check_id_format(id: &str) -> Result<String, Error> {
+    let is_well_formed = id.strip_prefix("0x").is_some_and(|digits| {
+        digits.len() == ID_WIDTH && digits.chars().all(|char| char.is_ascii_hexdigit())
+    });
+    if !is_well_formed {
+        return Err(background_error(
+            BackgroundErrorKey::MalformedId,
+            format!(
+                "Background id [[{}]] must be 0x followed by {} hexadecimal digits",
+                id, ID_WIDTH
+            ),
+            id,
+        ));
+    }
+
+    Ok(normalize_id(id))
+}
+
+/// Get why a background is retired and what replaces it, or `None` if it is active.
+pub fn get_retired(id: &str) -> Option<RetiredBackground> {
+    let background = find_background_by_id(id)?;
+    Some(RetiredBackground {
+        id: background.id.to_string(),
+        name: background.name.to_string(),
+        reason: background.get_retirement_reason()?,
+        successor: background.successor.map(String::from),
+    })
+}
+
+/// Check that a background id is well formed and either an active background in the catalog
+/// or a custom background, and get it normalized.
+pub fn validate_background_id(id: &str, custom: &[CustomBackground]) -> Result<String, Error> {
+    let id = &check_id_format(id)?;
+
+    if let Some(retired) = get_retired(id) {
+        let replacement = retired
+            .successor
+            .as_deref()
+            .and_then(find_background_by_id)
+            .map(|successor| format!(", try [[{}]] instead", successor.name))
+            .unwrap_or_default();
+        return Err(background_error(
+            BackgroundErrorKey::Retired,
+            format!("{}{}", retired.reason, replacement),
+            id,
+        ));
+    }
+
+    if !is_known_id(id, custom) {
+        return Err(background_error(
+            BackgroundErrorKey::UnknownId,
+            format!("Background [[{}]] is not a known background", id),
+            id,
+        ));
+    }
+
+    Ok(id.to_string())
+}
+
+fn background_error(error_key: BackgroundErrorKey, message: String, id: &str) -> Error {
+    Error::Custom(
+        serde_json::to_string(&BackgroundError {
+            error_key,
+            message,
+            id: id.to_string(),
+        })
+        .unwrap(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_ids_ignoring_case() {
+        let id = BACKGROUNDS[0].id;
+        let lowercase = id.to_ascii_lowercase();
+
+        assert_eq!(find_background_by_id(&lowercase).map(|bg| bg.id), Some(id));
+        assert!(is_known_id(&lowercase, &[]));
+        assert_eq!(check_id_format(&lowercase).unwrap(), id);
+    }
+
+    #[test]
+    fn finds_custom_duplicates_ignoring_case() {
+        let custom = [CustomBackground {
+            id: "0x0800000000ABCDEF".to_string(),
+            name: "Custom".to_string(),
+            image: None,
+        }];
+
+        assert!(is_known_id("0x0800000000abcdef", &custom));
+        assert_eq!(
+            validate_background_id("0x0800000000abcdef", &custom).unwrap(),
+            "0x0800000000ABCDEF"
+        );
+    }
+
+    fn custom_background(name: &str) -> CustomBackground {
+        CustomBackground {
+            id: "0x0800000000ABCDEF".to_string(),
+            name: name.to_string(),
+            image: None,
+        }
+    }
+
+    #[test]
+    fn counts_single_character_edits() {
+        assert_eq!(edit_distance("kiriko", "kiriko"), 0);
+        assert_eq!(edit_distance("kirko", "kiriko"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn tolerates_typos_in_longer_words() {
+        assert!(fuzzy_matches("kirko", "Amaterasu Kiriko"));
+        assert!(fuzzy_matches("hallowen terrer", "Halloween Terror 2023"));
+        assert!(fuzzy_matches("d.va", "D.Va Mythic"));
+        assert!(!fuzzy_matches("mai", "Mei"));
+        assert!(!fuzzy_matches("halowen", "Halloween"));
+
+        let filter = BackgroundFilter {
+            include_retired: true,
+            ..Default::default()
+        };
+        let results = search_backgrounds("amaterasu kirko", &filter, &[], &[]);
+        assert!(results
+            .iter()
+            .any(|background| background.name == "Amaterasu Kiriko"));
+    }
+
+    #[test]
+    fn filters_by_hero_event_and_season() {
+        let filter = BackgroundFilter {
+            hero: Some("kiriko".to_string()),
+            include_retired: true,
+            ..Default::default()
+        };
+        let results = search_backgrounds("", &filter, &[], &[]);
+        assert!(!results.is_empty());
+        assert!(results
+            .iter()
+            .all(|background| background.heroes.contains(&"Kiriko")));
+
+        let filter = BackgroundFilter {
+            event: Some("halloween terror 2023".to_string()),
+            include_retired: true,
+            ..Default::default()
+        };
+        let results = search_backgrounds("", &filter, &[], &[]);
+        assert!(!results.is_empty());
+        assert!(results
+            .iter()
+            .all(|background| background.event == Some("Halloween Terror 2023")));
+
+        let filter = BackgroundFilter {
+            season: Some(1),
+            include_retired: true,
+            ..Default::default()
+        };
+        let results = search_backgrounds("", &filter, &[], &[]);
+        assert!(!results.is_empty());
+        assert!(results
+            .iter()
+            .all(|background| background.season == Some(1)));
+    }
+
+    #[test]
+    fn matches_years_exactly() {
+        assert!(fuzzy_matches("2023", "Halloween Terror 2023"));
+        assert!(!fuzzy_matches("2023", "Halloween Terror 2024"));
+
+        let filter = BackgroundFilter {
+            include_retired: true,
+            ..Default::default()
+        };
+        let results = search_backgrounds("halloween 2023", &filter, &[], &[]);
+        assert!(!results.is_empty());
+        assert!(results
+            .iter()
+            .all(|background| background.event == Some("Halloween Terror 2023")));
+    }
+
+    #[test]
+    fn hides_retired_backgrounds_unless_requested() {
+        let kiriko = "0x0800000000000EF3";
+        let results = search_backgrounds("kiriko", &BackgroundFilter::default(), &[], &[]);
+        assert!(results.iter().all(|background| background.id != kiriko));
+
+        let filter = BackgroundFilter {
+            include_retired: true,
+            ..Default::default()
+        };
+        let results = search_backgrounds("kiriko", &filter, &[], &[]);
+        assert!(results.iter().any(|background| background.id == kiriko));
+    }
+
+    #[test]
+    fn lists_newest_first_and_custom_last() {
+        let custom = [custom_background("My Background")];
+        let results = search_backgrounds("", &BackgroundFilter::default(), &custom, &[]);
+
+        let (last, catalog) = results.split_last().unwrap();
+        assert!(last.custom);
+        assert!(catalog
+            .windows(2)
+            .all(|pair| pair[0].release_date >= pair[1].release_date));
+
+        let filter = BackgroundFilter {
+            has_music: Some(true),
+            ..Default::default()
+        };
+        let results = search_backgrounds("", &filter, &custom, &[]);
+        assert!(results.iter().all(|background| !background.custom));
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn picks_rotation_candidates_without_repeats() {
+        let pool = ids(&["0x01", "0x02", "0x03", "0x04"]);
+        let mut used = ids(&["0x01"]);
+
+        let candidates = pick_rotation_candidates(&pool, &mut used, &ids(&["0x02"]));
+        assert_eq!(candidates, ids(&["0x03", "0x04"]));
+        assert_eq!(used, ids(&["0x01"]));
+    }
+
+    #[test]
+    fn starts_a_new_rotation_round_when_every_background_was_used() {
+        let pool = ids(&["0x01", "0x02", "0x03"]);
+        let mut used = ids(&["0x01", "0x03"]);
+
+        let candidates = pick_rotation_candidates(&pool, &mut used, &ids(&["0x02"]));
+        assert_eq!(candidates, ids(&["0x01", "0x03"]));
+        assert!(used.is_empty());
+    }
+
+    #[test]
+    fn has_no_rotation_candidates_when_only_the_current_background_is_left() {
+        let pool = ids(&["0x01"]);
+        let mut used = vec![];
+
+        assert!(pick_rotation_candidates(&pool, &mut used, &ids(&["0x01"])).is_empty());
+    }
 }
@@ -1,3 +1,4 @@
+use crate::backgrounds::Tag;
 use crate::helpers::Error;
 use serde_json::Value;
 use std::fs;
@@ -18,11 +19,49 @@ pub struct SetupError {
     pub platforms: Option<Vec<String>>,
 }
 
+#[derive(serde::Serialize)]
+pub enum BackgroundErrorKey {
+    MalformedId,
+    UnknownId,
+    Retired,
+}
+/// Why a background id was rejected before being written to the launch arguments.
+#[derive(serde::Serialize)]
+pub struct BackgroundError {
+    pub error_key: BackgroundErrorKey,
+    pub message: String,
+    pub id: String,
+}
+
+/// Whether to restart a client after closing it to apply changes.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+pub enum RestartPolicy {
+    Never,
+    Ask,
+    #[default]
+    Always,
+}
+
+/// A Wine prefix with Battle.net installed, e.g. one managed by Lutris or Bottles.
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct WinePrefix {
+    pub path: String,
+    pub config: String,
+    pub install: Option<String>,
+    /// The Wine binary Battle.net was last seen running with in this prefix.
+    #[serde(default)]
+    pub runner: Option<String>,
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct BattleNetConfig {
     pub enabled: bool,
     pub config: Option<String>,
     pub install: Option<String>,
+    #[serde(default)]
+    pub prefixes: Vec<WinePrefix>,
+    #[serde(default)]
+    pub restart: RestartPolicy,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -34,9 +73,34 @@ pub struct SteamLocalconfig {
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct SteamProfile {
     pub id: String,
+    pub steam_id: Option<String>,
     pub name: String,
+    pub account_name: Option<String>,
+    /// URL of the avatar on the Steam CDN, the cached copy is read on demand.
     pub avatar: Option<String>,
     pub has_overwatch: bool,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(serde::Serialize, serde::Deserialize, PartialEq)]
+pub enum SteamInstallState {
+    Installed,
+    UpdateRequired,
+    Updating,
+    Incomplete,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct SteamOverwatchInstall {
+    pub library: String,
+    pub install_dir: String,
+    pub build_id: Option<String>,
+    pub state: SteamInstallState,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -47,31 +111,202 @@ pub struct SteamConfig {
     pub install: Option<String>,
     pub configs: Option<Vec<SteamLocalconfig>>,
     pub profiles: Option<Vec<SteamProfile>>,
+    pub overwatch: Option<SteamOverwatchInstall>,
+    #[serde(default)]
+    pub restart: RestartPolicy,
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
 pub struct BackgroundConfig {
     pub current: Option<String>,
     pub is_outdated: bool,
+    /// A background id set outside of OverBuddy that can be imported as a custom background.
+    #[serde(default)]
+    pub unknown: Option<String>,
+    /// Set when the current background is broken or removed from the game.
+    #[serde(default)]
+    pub retired: Option<RetiredBackground>,
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
+/// A background that no longer works, with the reason and the background replacing it.
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Debug)]
+pub struct RetiredBackground {
+    pub id: String,
+    pub name: String,
+    pub reason: String,
+    pub successor: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
 pub struct AdditionalConfig {
     pub console_enabled: bool,
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
 pub struct SharedConfig {
     pub background: BackgroundConfig,
     pub additional: AdditionalConfig,
 }
 
+/// A single place OverBuddy writes launch arguments to.
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "platform", content = "account")]
+pub enum Target {
+    BattleNet,
+    Steam(String),
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct TargetState {
+    pub target: Target,
+    pub background: BackgroundConfig,
+    pub additional: AdditionalConfig,
+}
+
+/// The state of a launch option on a target.
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct LaunchOptionState {
+    pub target: Target,
+    pub enabled: bool,
+    pub value: Option<String>,
+}
+
+/// A launch argument change that can be applied to targets.
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "kind", content = "value")]
+pub enum LaunchChange {
+    Background(Option<String>),
+    Console(bool),
+    LaunchOption {
+        name: String,
+        enabled: bool,
+        value: Option<String>,
+    },
+    Preset(Preset),
+}
+
+impl LaunchChange {
+    /// Check if this change overrides `other` when applied to the same targets.
+    pub fn replaces(&self, other: &LaunchChange) -> bool {
+        match (self, other) {
+            (
+                LaunchChange::LaunchOption { name, .. },
+                LaunchChange::LaunchOption {
+                    name: other_name, ..
+                },
+            ) => name == other_name,
+            // A preset sets the background, console and its own launch options
+            (LaunchChange::Preset(_), LaunchChange::Background(_) | LaunchChange::Console(_)) => {
+                true
+            }
+            (LaunchChange::Preset(preset), LaunchChange::LaunchOption { name, .. }) => {
+                preset.options.iter().any(|option| option.name == *name)
+            }
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+/// A background added by the user, e.g. one found before it is added to the catalog.
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Debug)]
+pub struct CustomBackground {
+    pub id: String,
+    pub name: String,
+    /// Path to a local image shown in place of a catalog image.
+    pub image: Option<String>,
+}
+
+/// A launch option set by a preset.
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Debug)]
+pub struct PresetOption {
+    pub name: String,
+    pub enabled: bool,
+    pub value: Option<String>,
+}
+
+/// A named set of launch arguments applied together, e.g. "streaming" or "scrim".
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Debug)]
+pub struct Preset {
+    pub name: String,
+    pub background: Option<String>,
+    pub console_enabled: bool,
+    #[serde(default)]
+    pub options: Vec<PresetOption>,
+}
+
+/// A named list of backgrounds put together by the user.
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Debug)]
+pub struct Collection {
+    pub name: String,
+    /// Background ids in the user's order, kept even if they leave the catalog.
+    pub backgrounds: Vec<String>,
+}
+
+/// The backgrounds a rotation picks from.
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "kind", content = "value")]
+pub enum RotationPool {
+    /// Every active background in the catalog.
+    Catalog,
+    /// Active backgrounds with every one of the tags.
+    Tags(Vec<Tag>),
+    /// Background ids chosen by the user.
+    Backgrounds(Vec<String>),
+}
+
+/// When a rotation picks the next background.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+pub enum RotationTrigger {
+    #[default]
+    Startup,
+    GameExit,
+}
+
+/// Sets a different background each session, e.g. when OverBuddy starts.
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Debug)]
+pub struct RotationConfig {
+    pub pool: RotationPool,
+    pub trigger: RotationTrigger,
+    /// Backgrounds already picked since the pool was last exhausted.
+    #[serde(default)]
+    pub used: Vec<String>,
+}
+
+/// A change waiting for its client to exit before it is applied.
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct PendingChange {
+    pub id: u64,
+    pub targets: Vec<Target>,
+    pub change: LaunchChange,
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct Config {
     pub is_setup: bool,
     pub battle_net: BattleNetConfig,
     pub steam: SteamConfig,
     pub shared: SharedConfig,
+    pub targets: Vec<TargetState>,
+    #[serde(default)]
+    pub defer_changes: bool,
+    #[serde(default)]
+    pub pending: Vec<PendingChange>,
+    #[serde(default)]
+    pub presets: Vec<Preset>,
+    #[serde(default)]
+    pub custom_backgrounds: Vec<CustomBackground>,
+    /// Catalog ids the user has already seen, or `None` before the first launch that
+    /// records them.
+    #[serde(default)]
+    pub seen_backgrounds: Option<Vec<String>>,
+    /// Favourite background ids in the user's order.
+    #[serde(default)]
+    pub favourites: Vec<String>,
+    #[serde(default)]
+    pub collections: Vec<Collection>,
+    /// Background rotation, or `None` to keep the background until the user changes it.
+    #[serde(default)]
+    pub rotation: Option<RotationConfig>,
 }
 
 pub fn get_default_config() -> Config {
@@ -81,6 +316,8 @@ pub fn get_default_config() -> Config {
             enabled: false,
             config: None,
             install: None,
+            prefixes: vec![],
+            restart: RestartPolicy::Always,
         },
         steam: SteamConfig {
             enabled: false,
@@ -89,16 +326,29 @@ pub fn get_default_config() -> Config {
             install: None,
             configs: None,
             profiles: None,
+            overwatch: None,
+            restart: RestartPolicy::Always,
         },
         shared: SharedConfig {
             background: BackgroundConfig {
                 current: None,
                 is_outdated: false,
+                unknown: None,
+                retired: None,
             },
             additional: AdditionalConfig {
                 console_enabled: false,
             },
         },
+        targets: vec![],
+        defer_changes: false,
+        pending: vec![],
+        presets: vec![],
+        custom_backgrounds: vec![],
+        seen_backgrounds: None,
+        favourites: vec![],
+        collections: vec![],
+        rotation: None,
     }
 }
 
@@ -1,7 +1,13 @@
-use serde::Serialize;
-use serde_json::{from_reader, Serializer, Value};
-use std::fs;
-use std::io;
+use crate::backgrounds;
+use crate::config::{AdditionalConfig, BackgroundConfig, CustomBackground, Preset, SharedConfig};
+use crate::journal;
+use crate::json_edit;
+use crate::launch_args::LaunchArgs;
+use crate::launch_options::{self, LaunchOption};
+use serde_json::Value;
+use similar::{ChangeTag, TextDiff};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader};
 use std::path::{Path, PathBuf};
 
 // Global helpers
@@ -43,160 +49,270 @@ pub fn get_file_name_from_path(path: &str) -> Option<&str> {
     Path::new(path).file_name().and_then(|name| name.to_str())
 }
 
-pub fn safe_json_write(path: String, json: &serde_json::Value) -> Result<(), Error> {
-    // Create backup
-    let backup_path = format!("{}.backup", path);
-    if Path::new(&path).exists() {
-        fs::copy(&path, &backup_path).map_err(|e| {
-            Error::Custom(format!(
-                "Failed to create backup of [[{}]]: {}",
-                get_file_name_from_path(&path).unwrap_or("unknown"),
-                e
-            ))
-        })?;
-    }
-
-    let cleanup = |replace: bool| -> Result<(), Error> {
-        if Path::new(&backup_path).exists() {
-            if replace {
-                fs::copy(&backup_path, &path).map_err(|_| {
+/// Read the contents of files before changing them, so the change can be undone.
+pub fn read_originals(files: &[&str]) -> Result<Vec<(String, String)>, Error> {
+    files
+        .iter()
+        .map(|file| {
+            fs::read_to_string(file)
+                .map(|contents| (file.to_string(), contents))
+                .map_err(|e| {
                     Error::Custom(format!(
-                        "Failed to restore backup of [[{}]]",
-                        get_file_name_from_path(&path).unwrap_or("unknown")
+                        "Failed to read [[{}]] before changing it: {}",
+                        file, e
                     ))
-                })?;
-            }
-            let _ = fs::remove_file(&backup_path);
+                })
+        })
+        .collect()
+}
+
+/// Write back the original contents of files that have changed since they were read.
+pub fn restore_originals(originals: &[(String, String)]) -> Result<(), Error> {
+    for (file, contents) in originals {
+        if fs::read_to_string(file).is_ok_and(|current| current == *contents) {
+            continue;
         }
-        Ok(())
-    };
+        journal::write(file, contents, |_| Ok(()))?;
+    }
+    Ok(())
+}
 
-    // Write to file
-    let mut file = match fs::OpenOptions::new()
-        .write(true)
-        .truncate(true)
-        .open(&path)
-    {
-        Ok(file) => file,
-        Err(_) => {
-            cleanup(false)?;
+/// Check if any of the files changed since they were read.
+pub fn has_changed(originals: &[(String, String)]) -> bool {
+    originals
+        .iter()
+        .any(|(file, contents)| !fs::read_to_string(file).is_ok_and(|current| current == *contents))
+}
+
+/// Set a string value in a JSON file, leaving every other byte of the file untouched.
+pub fn safe_json_edit(path: &str, keys: &[&str], value: &str) -> Result<(), Error> {
+    let file_name = get_file_name_from_path(path).unwrap_or("unknown");
+
+    let contents = fs::read_to_string(path).map_err(|e| {
+        Error::Custom(format!(
+            "Failed to open [[{}]] file at [[{}]]: {}",
+            file_name, path, e
+        ))
+    })?;
+    let new_contents = match json_edit::set_str(&contents, keys, value) {
+        Ok(Some(new_contents)) => new_contents,
+        Ok(None) => {
             return Err(Error::Custom(format!(
-                "Failed to open [[{}]] file at [[{}]]",
-                get_file_name_from_path(&path).unwrap_or("unknown"),
+                "Failed to find [[{}]] in [[{}]] file at [[{}]]",
+                keys[..keys.len() - 1].join("/"),
+                file_name,
                 path
             )));
         }
-    };
-    let pretty_formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
-    let mut serializer = Serializer::with_formatter(&mut file, pretty_formatter);
-    match json.serialize(&mut serializer) {
-        Ok(_) => (),
-        Err(_) => {
-            cleanup(false)?;
-            return Err(Error::Custom(format!("Failed to write to [[{}]]", path)));
+        Err(e) => {
+            return Err(Error::Custom(format!(
+                "Failed to read [[{}]] file at [[{}]]: {}",
+                file_name, path, e
+            )));
         }
+    };
+    if new_contents == contents {
+        return Ok(());
     }
-
-    // Validate new config
-    let file = match fs::File::open(&path) {
-        Ok(file) => file,
-        Err(_) => {
-            // Restore backup
-            cleanup(true)?;
+    // Check that the edit changed nothing but the value at the key path
+    let parse = |contents: &str| {
+        serde_json::from_str::<Value>(contents.trim_start_matches('\u{feff}')).ok()
+    };
+    let expected = parse(&contents).map(|mut json| {
+        let parent = keys[..keys.len() - 1]
+            .iter()
+            .try_fold(&mut json, |json, key| json.get_mut(*key));
+        if let Some(Value::Object(parent)) = parent {
+            parent.insert(keys[keys.len() - 1].to_string(), Value::from(value));
+        }
+        json
+    });
+    match parse(&new_contents) {
+        None => {
             return Err(Error::Custom(format!(
-                "Failed to open [[{}]] file at [[{}]]",
-                get_file_name_from_path(&path).unwrap_or("unknown"),
+                "Failed to write to [[{}]]: the result is not valid JSON",
                 path
             )));
         }
+        Some(json) if expected.is_some_and(|expected| expected != json) => {
+            return Err(Error::Custom(format!(
+                "Failed to write to [[{}]]: the result changes more than [[{}]]",
+                path,
+                keys.join("/")
+            )));
+        }
+        _ => {}
+    }
+
+    // Write and verify config file
+    let key = keys.last().copied().unwrap_or_default();
+    journal::write(path, &new_contents, |temp_path| {
+        verify_file_diff(path, temp_path, key)
+            .map(|_| ())
+            .map_err(|e| {
+                Error::Custom(format!(
+                    "Failed to verify the temporary file at [[{}]], {}",
+                    temp_path, e
+                ))
+            })
+    })
+}
+
+/// Check that the only line changed between two files is the one containing `key`.
+///
+/// Returns whether the files differ.
+pub fn verify_file_diff(file1: &str, file2: &str, key: &str) -> Result<bool, String> {
+    let read_lines = |filename: &str| -> io::Result<Vec<String>> {
+        let file = File::open(filename)?;
+        let reader = BufReader::new(file);
+        reader.lines().collect()
     };
 
-    let parsed_json: Result<Value, _> = from_reader(file);
-    match parsed_json {
-        Ok(_) => {
-            // JSON is good, remove the backup
-            cleanup(false)?;
-            Ok(())
+    let lines1 = read_lines(file1)
+        .map_err(|e| format!("Failed to read [[{}]]: {}", file1, e))?
+        .join("\n");
+    let lines2 = read_lines(file2)
+        .map_err(|e| format!("Failed to read [[{}]]: {}", file2, e))?
+        .join("\n");
+
+    let diff = TextDiff::from_lines(&lines1, &lines2);
+
+    let mut insert_count = 0;
+    let mut delete_count = 0;
+    for change in diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => {
+                if !change.value().contains(key) {
+                    return Err(format!(
+                        "Tried to incorrectly insert [[{}]]",
+                        change.to_string_lossy()
+                    ));
+                }
+                insert_count += 1;
+            }
+            ChangeTag::Delete => {
+                if !change.value().contains(key) {
+                    return Err(format!(
+                        "Tried to incorrectly delete [[{}]]",
+                        change.to_string_lossy()
+                    ));
+                }
+                delete_count += 1;
+            }
+            _ => {}
         }
-        Err(_) => {
-            // JSON is corrupted, restore the backup
-            cleanup(true)?;
-            Err(Error::Io(std::io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!("Failed to write to [[{}]]", path),
-            )))
+
+        if insert_count > 1 || delete_count > 1 {
+            return Err("More than one line is different".to_string());
         }
     }
+
+    if insert_count == 0 {
+        Ok(false)
+    } else {
+        Ok(true)
+    }
 }
 
 // Launch argument helpers
 
+/// Get the OverBuddy state from the launch arguments.
+pub fn get_launch_state(launch_args: &str, custom: &[CustomBackground]) -> SharedConfig {
+    let current_background = get_background(launch_args);
+    let resolved_background = current_background
+        .as_ref()
+        .filter(|current_background_id| backgrounds::is_known_id(current_background_id, custom))
+        .map(|current_background_id| backgrounds::normalize_id(current_background_id));
+    // Ids that could be a background are offered for import
+    let unknown_background = current_background
+        .clone()
+        .filter(|id| resolved_background.is_none() && backgrounds::check_id_format(id).is_ok());
+
+    SharedConfig {
+        background: BackgroundConfig {
+            is_outdated: resolved_background.is_none() && current_background.is_some(),
+            retired: resolved_background
+                .as_deref()
+                .and_then(backgrounds::get_retired),
+            current: resolved_background,
+            unknown: unknown_background,
+        },
+        additional: AdditionalConfig {
+            console_enabled: get_console_enabled(launch_args),
+        },
+    }
+}
+
 /// Get the background id from the launch arguments.
 pub fn get_background(launch_args: &str) -> Option<String> {
-    launch_args
-        .split_whitespace()
-        .find(|s| s.starts_with(format!("{}=", BACKGROUND_LAUNCH_ARG).as_str()))
-        .and_then(|s| s.split('=').nth(1))
-        .and_then(|s| {
-            if s.is_empty() {
-                None
-            } else {
-                Some(String::from(s))
-            }
-        })
+    LaunchArgs::parse(launch_args)
+        .get_value(BACKGROUND_LAUNCH_ARG)
+        .filter(|id| !id.is_empty())
+        .map(String::from)
 }
 
 /// Get the debug console state from the launch arguments.
 pub fn get_console_enabled(launch_args: &str) -> bool {
-    launch_args
-        .split_whitespace()
-        .any(|s| s == CONSOLE_LAUNCH_ARG)
+    LaunchArgs::parse(launch_args).has(CONSOLE_LAUNCH_ARG)
 }
 
-const BACKGROUND_LAUNCH_ARG: &str = "--lobbyMap";
+const BACKGROUND_LAUNCH_ARG: &str = launch_options::LOBBY_MAP;
 /// Generate background launch arguments
 pub fn generate_background_launch_args(launch_args: Option<&str>, id: Option<&str>) -> String {
-    let new_arg = id.map(|id| format!("{}={}", BACKGROUND_LAUNCH_ARG, id));
-
-    let filtered_args = launch_args
-        .map(|args| {
-            args.split_whitespace()
-                .filter(|&part| !part.starts_with(BACKGROUND_LAUNCH_ARG))
-                .collect::<Vec<_>>()
-                .join(" ")
-        })
-        .unwrap_or_default();
-
-    match (filtered_args.is_empty(), new_arg) {
-        (true, Some(arg)) => arg,
-        (false, Some(arg)) => format!("{} {}", filtered_args, arg),
-        (false, None) => filtered_args,
-        (true, None) => String::new(),
+    let mut launch_args = LaunchArgs::parse(launch_args.unwrap_or_default());
+    match id {
+        Some(id) => launch_args.set_value(BACKGROUND_LAUNCH_ARG, id),
+        None => launch_args.remove(BACKGROUND_LAUNCH_ARG),
     }
+
+    launch_args.to_string()
 }
 
-const CONSOLE_LAUNCH_ARG: &str = "--tank_Console";
+const CONSOLE_LAUNCH_ARG: &str = launch_options::TANK_CONSOLE;
 /// Generate debug console launch arguments
 pub fn generate_console_launch_args(launch_args: Option<&str>, enable_console: bool) -> String {
-    let new_arg = if enable_console {
-        Some(CONSOLE_LAUNCH_ARG.to_string())
+    let mut launch_args = LaunchArgs::parse(launch_args.unwrap_or_default());
+    if enable_console {
+        launch_args.set_flag(CONSOLE_LAUNCH_ARG);
     } else {
-        None
-    };
+        launch_args.remove(CONSOLE_LAUNCH_ARG);
+    }
 
-    let filtered_args = launch_args
-        .map(|args| {
-            args.split_whitespace()
-                .filter(|&part| part != CONSOLE_LAUNCH_ARG)
-                .collect::<Vec<_>>()
-                .join(" ")
-        })
-        .unwrap_or_default();
+    launch_args.to_string()
+}
+
+/// Generate launch arguments for an option from the launch options registry
+pub fn generate_launch_option_args(
+    launch_args: Option<&str>,
+    (option, enabled, value): (&LaunchOption, bool, Option<&str>),
+) -> String {
+    let mut launch_args = LaunchArgs::parse(launch_args.unwrap_or_default());
+    option.set(&mut launch_args, enabled, value);
 
-    match (filtered_args.is_empty(), new_arg) {
-        (true, Some(arg)) => arg,
-        (false, Some(arg)) => format!("{} {}", filtered_args, arg),
-        (false, None) => filtered_args,
-        (true, None) => String::new(),
+    launch_args.to_string()
+}
+
+/// Generate launch arguments for a preset, applying all of its options in one pass
+pub fn generate_preset_launch_args(launch_args: Option<&str>, preset: &Preset) -> String {
+    let mut launch_args = LaunchArgs::parse(launch_args.unwrap_or_default());
+    match &preset.background {
+        Some(id) => launch_args.set_value(BACKGROUND_LAUNCH_ARG, id),
+        None => launch_args.remove(BACKGROUND_LAUNCH_ARG),
+    }
+    if preset.console_enabled {
+        launch_args.set_flag(CONSOLE_LAUNCH_ARG);
+    } else {
+        launch_args.remove(CONSOLE_LAUNCH_ARG);
     }
+    for preset_option in &preset.options {
+        if let Some(option) = launch_options::find_launch_option(&preset_option.name) {
+            option.set(
+                &mut launch_args,
+                preset_option.enabled,
+                preset_option.value.as_deref(),
+            );
+        }
+    }
+
+    launch_args.to_string()
 }
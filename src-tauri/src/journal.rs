@@ -0,0 +1,353 @@
+use crate::helpers::{self, Error};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+// Crash-safe file writes
+//
+// Each write copies the original file to `<file>.backup`, writes the new contents to
+// `<file>.tmp` and, once the temp file is verified, records both in a journal before it is
+// renamed over the original. A journal entry that is still around on startup means a write was interrupted.
+
+static JOURNAL_FILE: &str = "journal.json";
+static JOURNAL_DIR: OnceLock<PathBuf> = OnceLock::new();
+/// Serializes reading and rewriting the journal between concurrent writes.
+static JOURNAL_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+struct JournalEntry {
+    file: String,
+    backup: String,
+    temp: String,
+}
+
+impl JournalEntry {
+    fn new(file: &str) -> Self {
+        JournalEntry {
+            file: file.to_string(),
+            backup: format!("{}.backup", file),
+            temp: format!("{}.tmp", file),
+        }
+    }
+}
+
+/// A write that did not complete, either recorded in the journal or found as a leftover
+/// backup file.
+#[derive(serde::Serialize, Clone)]
+pub struct InterruptedWrite {
+    pub file: String,
+    pub journaled: bool,
+    pub has_backup: bool,
+    pub has_temp: bool,
+}
+
+/// Set the directory the journal is stored in.
+pub fn init(dir: PathBuf) {
+    let _ = JOURNAL_DIR.set(dir);
+}
+
+/// Replace the contents of a file, journaling the write so it can be recovered after a crash.
+///
+/// `verify` is called with the path of the fully written temp file before it replaces `path`.
+pub fn write<F>(path: &str, contents: &str, verify: F) -> Result<(), Error>
+where
+    F: FnOnce(&str) -> Result<(), Error>,
+{
+    let file_name = helpers::get_file_name_from_path(path).unwrap_or("unknown");
+    let entry = JournalEntry::new(path);
+
+    // Backup original file
+    fs::copy(path, &entry.backup)
+        .and_then(|_| File::open(&entry.backup)?.sync_all())
+        .map_err(|e| {
+            Error::Custom(format!(
+                "Failed to create backup of [[{}]]: {}",
+                file_name, e
+            ))
+        })?;
+
+    // Write new contents to temp file
+    if let Err(e) = write_synced(&entry.temp, contents) {
+        discard(&entry);
+        return Err(Error::Custom(format!(
+            "Failed to write to the temporary file at [[{}]]: {}",
+            entry.temp, e
+        )));
+    }
+
+    // Verify temp file before recording it, so only verified writes can be completed
+    if let Err(e) = verify(&entry.temp) {
+        discard(&entry);
+        return Err(e);
+    }
+
+    // Record write
+    if let Err(e) = add_entry(&entry) {
+        discard(&entry);
+        return Err(e);
+    }
+
+    // Replace original file
+    if let Err(e) = fs::rename(&entry.temp, path) {
+        discard(&entry);
+        return Err(Error::Custom(format!(
+            "Failed to replace [[{}]] with temporary file. {}",
+            path, e
+        )));
+    }
+    sync_parent(path);
+
+    // Complete write
+    let _ = fs::remove_file(&entry.backup);
+    remove_entry(path)
+}
+
+/// Get all interrupted writes, including leftover backups of the given files.
+pub fn get_interrupted_writes(files: &[&str]) -> Vec<InterruptedWrite> {
+    let mut writes: Vec<InterruptedWrite> = read_entries()
+        .iter()
+        .map(|entry| InterruptedWrite {
+            file: entry.file.clone(),
+            journaled: true,
+            has_backup: Path::new(&entry.backup).exists(),
+            has_temp: Path::new(&entry.temp).exists(),
+        })
+        .collect();
+
+    for file in files {
+        let entry = JournalEntry::new(file);
+        if Path::new(&entry.backup).exists() && !writes.iter().any(|write| write.file == *file) {
+            writes.push(InterruptedWrite {
+                file: entry.file,
+                journaled: false,
+                has_backup: true,
+                has_temp: Path::new(&entry.temp).exists(),
+            });
+        }
+    }
+
+    writes
+}
+
+/// Recover an interrupted write.
+///
+/// Rolling forward completes a journaled write, or keeps the current file if the write was
+/// not journaled. Rolling back restores the backup of the original file.
+pub fn recover(write: &InterruptedWrite, roll_forward: bool) -> Result<(), Error> {
+    let entry = JournalEntry::new(&write.file);
+
+    if roll_forward {
+        if write.journaled && Path::new(&entry.temp).exists() {
+            fs::rename(&entry.temp, &entry.file).map_err(|e| {
+                Error::Custom(format!(
+                    "Failed to replace [[{}]] with temporary file. {}",
+                    entry.file, e
+                ))
+            })?;
+            sync_parent(&entry.file);
+        }
+    } else {
+        if !Path::new(&entry.backup).exists() {
+            return Err(Error::Custom(format!(
+                "Failed to find a backup of [[{}]] to restore",
+                entry.file
+            )));
+        }
+        fs::copy(&entry.backup, &entry.temp)
+            .and_then(|_| File::open(&entry.temp)?.sync_all())
+            .and_then(|_| fs::rename(&entry.temp, &entry.file))
+            .map_err(|e| {
+                Error::Custom(format!(
+                    "Failed to restore backup of [[{}]]: {}",
+                    entry.file, e
+                ))
+            })?;
+        sync_parent(&entry.file);
+    }
+
+    discard(&entry);
+    Ok(())
+}
+
+/// Remove the backup and temp files of a write and its journal entry.
+fn discard(entry: &JournalEntry) {
+    let _ = fs::remove_file(&entry.temp);
+    let _ = fs::remove_file(&entry.backup);
+    let _ = remove_entry(&entry.file);
+}
+
+fn write_synced(path: &str, contents: &str) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()
+}
+
+/// Persist a rename by syncing the directory it happened in.
+fn sync_parent(path: &str) {
+    #[cfg(unix)]
+    if let Some(parent) = Path::new(path).parent() {
+        let _ = File::open(parent).and_then(|dir| dir.sync_all());
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+fn get_journal_path() -> Result<PathBuf, Error> {
+    JOURNAL_DIR
+        .get()
+        .map(|dir| dir.join(JOURNAL_FILE))
+        .ok_or_else(|| Error::Custom("Failed to find the write journal".to_string()))
+}
+
+fn read_entries() -> Vec<JournalEntry> {
+    get_journal_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|journal| serde_json::from_str(&journal).ok())
+        .unwrap_or_default()
+}
+
+fn write_entries(entries: &[JournalEntry]) -> Result<(), Error> {
+    let path = get_journal_path()?;
+    let temp_path = path.with_extension("json.tmp");
+    let temp_path = temp_path.to_string_lossy();
+
+    write_synced(&temp_path, &serde_json::to_string(entries)?)
+        .and_then(|_| fs::rename(temp_path.as_ref(), &path))
+        .map_err(|e| {
+            Error::Custom(format!(
+                "Failed to write the write journal at [[{}]]: {}",
+                path.display(),
+                e
+            ))
+        })
+}
+
+fn add_entry(entry: &JournalEntry) -> Result<(), Error> {
+    let _lock = JOURNAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut entries = read_entries();
+    entries.retain(|other| other.file != entry.file);
+    entries.push(entry.clone());
+    write_entries(&entries)
+}
+
+fn remove_entry(file: &str) -> Result<(), Error> {
+    let _lock = JOURNAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut entries = read_entries();
+    if !entries.iter().any(|entry| entry.file == file) {
+        return Ok(());
+    }
+    entries.retain(|entry| entry.file != file);
+    write_entries(&entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Get a path for a test file in the journal directory, with no leftovers from earlier runs.
+    fn test_file(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("overbuddy-journal-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        init(dir.clone());
+
+        let path = dir.join(name).to_string_lossy().to_string();
+        discard(&JournalEntry::new(&path));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    /// Leave a file as if a write to it was interrupted after the temp file was written.
+    fn interrupt_write(path: &str, journaled: bool) -> JournalEntry {
+        let entry = JournalEntry::new(path);
+        fs::write(path, "original").unwrap();
+        fs::write(&entry.backup, "original").unwrap();
+        fs::write(&entry.temp, "changed").unwrap();
+        if journaled {
+            add_entry(&entry).unwrap();
+        }
+        entry
+    }
+
+    fn get_write(path: &str) -> Option<InterruptedWrite> {
+        get_interrupted_writes(&[path])
+            .into_iter()
+            .find(|write| write.file == path)
+    }
+
+    #[test]
+    fn writes_and_cleans_up() {
+        let path = test_file("write.txt");
+        fs::write(&path, "original").unwrap();
+
+        write(&path, "changed", |_| Ok(())).unwrap();
+
+        let entry = JournalEntry::new(&path);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "changed");
+        assert!(!Path::new(&entry.backup).exists());
+        assert!(!Path::new(&entry.temp).exists());
+        assert!(get_write(&path).is_none());
+    }
+
+    #[test]
+    fn keeps_the_original_when_verification_fails() {
+        let path = test_file("verify.txt");
+        fs::write(&path, "original").unwrap();
+
+        let result = write(&path, "changed", |temp| {
+            // Nothing unverified may be journaled
+            assert!(!read_entries().iter().any(|entry| entry.file == path));
+            assert_eq!(fs::read_to_string(temp).unwrap(), "changed");
+            Err(Error::Custom("invalid".to_string()))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "original");
+        assert!(get_write(&path).is_none());
+    }
+
+    #[test]
+    fn rolls_forward_journaled_writes() {
+        let path = test_file("forward.txt");
+        let entry = interrupt_write(&path, true);
+
+        let write = get_write(&path).unwrap();
+        assert!(write.journaled && write.has_backup && write.has_temp);
+        recover(&write, true).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "changed");
+        assert!(!Path::new(&entry.backup).exists());
+        assert!(!Path::new(&entry.temp).exists());
+        assert!(get_write(&path).is_none());
+    }
+
+    #[test]
+    fn rolls_back_journaled_writes() {
+        let path = test_file("back.txt");
+        let entry = interrupt_write(&path, true);
+        fs::write(&path, "half written").unwrap();
+
+        recover(&get_write(&path).unwrap(), false).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "original");
+        assert!(!Path::new(&entry.temp).exists());
+        assert!(get_write(&path).is_none());
+    }
+
+    #[test]
+    fn detects_leftover_backups() {
+        let path = test_file("leftover.txt");
+        let entry = interrupt_write(&path, false);
+        fs::remove_file(&entry.temp).unwrap();
+        fs::write(&path, "changed").unwrap();
+
+        let write = get_write(&path).unwrap();
+        assert!(!write.journaled && write.has_backup && !write.has_temp);
+
+        // Keeping the current file only removes the backup
+        recover(&write, true).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "changed");
+        assert!(get_write(&path).is_none());
+    }
+}
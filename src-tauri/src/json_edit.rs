@@ -0,0 +1,380 @@
+// Minimal edits to JSON documents
+//
+// Values are located by scanning the original text, so everything outside the edited
+// value keeps its exact bytes, key order and formatting.
+
+#[derive(Debug, thiserror::Error)]
+#[error("{message} at line {line}, column {column}")]
+pub struct ParseError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Set a string value by its key path, e.g. `["Games", "prometheus", "AdditionalLaunchArguments"]`.
+///
+/// A missing key is inserted as the first member of its parent object, using the
+/// indentation of the existing members. Returns `None` if a parent object does not exist.
+pub fn set_str(input: &str, path: &[&str], value: &str) -> Result<Option<String>, ParseError> {
+    let mut scanner = Scanner::new(input);
+    scanner.skip_whitespace();
+    let mut object_start = scanner.pos;
+
+    for (index, key) in path.iter().enumerate() {
+        scanner.pos = object_start;
+        let members = scanner.scan_object()?;
+        let member = members.iter().find(|member| member.key == *key);
+
+        if index < path.len() - 1 {
+            match member {
+                Some(member) if input[member.value_start..].starts_with('{') => {
+                    object_start = member.value_start;
+                }
+                _ => return Ok(None),
+            }
+            continue;
+        }
+
+        let encoded_value = serde_json::to_string(value).unwrap();
+        let output = match (member, members.first()) {
+            // Replace the existing value
+            (Some(member), _) => format!(
+                "{}{}{}",
+                &input[..member.value_start],
+                encoded_value,
+                &input[member.value_end..]
+            ),
+            // Insert before the first member, copying its formatting
+            (None, Some(first)) => format!(
+                "{}{}{}{},{}{}",
+                &input[..first.key_start],
+                serde_json::to_string(key).unwrap(),
+                &input[first.key_end..first.value_start],
+                encoded_value,
+                &input[object_start + 1..first.key_start],
+                &input[first.key_start..]
+            ),
+            // Insert into an empty object
+            (None, None) => format!(
+                "{}{}: {}{}",
+                &input[..object_start + 1],
+                serde_json::to_string(key).unwrap(),
+                encoded_value,
+                &input[object_start + 1..]
+            ),
+        };
+
+        return Ok(Some(output));
+    }
+
+    Ok(None)
+}
+
+struct Member {
+    key: String,
+    key_start: usize,
+    key_end: usize,
+    value_start: usize,
+    value_end: usize,
+}
+
+struct Scanner<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(input: &'a str) -> Self {
+        // Skip the byte order mark
+        let pos = if input.starts_with('\u{feff}') { 3 } else { 0 };
+        Scanner { input, pos }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\r' | b'\n') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), ParseError> {
+        if self.peek() != Some(expected) {
+            return Err(self.error(&format!("Expected '{}'", expected as char)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Scan an object and get the position of each member.
+    fn scan_object(&mut self) -> Result<Vec<Member>, ParseError> {
+        let mut members = vec![];
+        self.expect(b'{')?;
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(members);
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key_start = self.pos;
+            let key = self.scan_string()?;
+            let key_end = self.pos;
+            self.skip_whitespace();
+            self.expect(b':')?;
+            self.skip_whitespace();
+            let value_start = self.pos;
+            self.scan_value()?;
+            members.push(Member {
+                key,
+                key_start,
+                key_end,
+                value_start,
+                value_end: self.pos,
+            });
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(members);
+                }
+                _ => return Err(self.error("Expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn scan_array(&mut self) -> Result<(), ParseError> {
+        self.expect(b'[')?;
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(());
+        }
+
+        loop {
+            self.skip_whitespace();
+            self.scan_value()?;
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                _ => return Err(self.error("Expected ',' or ']'")),
+            }
+        }
+    }
+
+    /// Scan a quoted string and get its unescaped value.
+    fn scan_string(&mut self) -> Result<String, ParseError> {
+        let start = self.pos;
+        self.expect(b'"')?;
+        loop {
+            match self.peek() {
+                Some(b'"') => break,
+                Some(b'\\') => self.pos += 2,
+                Some(_) => self.pos += 1,
+                None => return Err(self.error("Unterminated string")),
+            }
+        }
+        self.pos += 1;
+
+        let raw = &self.input[start..self.pos];
+        serde_json::from_str(raw).map_err(|_| {
+            self.pos = start;
+            self.error("Invalid string")
+        })
+    }
+
+    fn scan_value(&mut self) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(b'"') => self.scan_string().map(|_| ()),
+            Some(b'{') => self.scan_object().map(|_| ()),
+            Some(b'[') => self.scan_array(),
+            _ => {
+                // Numbers, booleans and null
+                let start = self.pos;
+                while let Some(byte) = self.peek() {
+                    if matches!(byte, b',' | b'}' | b']' | b' ' | b'\t' | b'\r' | b'\n') {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                if self.pos == start {
+                    return Err(self.error("Expected a value"));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        let consumed = &self.input[..self.pos.min(self.input.len())];
+        let line = consumed.matches('\n').count() + 1;
+        let column = consumed.len() - consumed.rfind('\n').map_or(0, |index| index + 1) + 1;
+
+        ParseError {
+            message: message.to_string(),
+            line,
+            column,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"{
+    "Client": {
+        "Sound": {
+            "Enabled": "true"
+        }
+    },
+    "Games": {
+        "prometheus": {
+            "AdditionalLaunchArguments": "--tank_Console",
+            "LastActioned": "1700000000"
+        }
+    }
+}
+"#;
+
+    const LAUNCH_ARGS: [&str; 3] = ["Games", "prometheus", "AdditionalLaunchArguments"];
+
+    #[test]
+    fn replaces_an_existing_value() {
+        let output = set_str(CONFIG, &LAUNCH_ARGS, "--lobbyMap=0x0800000000000001")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            output,
+            CONFIG.replace("\"--tank_Console\"", "\"--lobbyMap=0x0800000000000001\"")
+        );
+    }
+
+    #[test]
+    fn keeps_bytes_outside_the_value_unchanged() {
+        let input = "{\r\n\t\"Games\" :{ \"prometheus\":{\"AdditionalLaunchArguments\" : \"old\" ,\"x\":[1, 2.5e3, true, null, {\"y\": \"}\"}]}},\r\n\t\"z\": \"\\\"old\\\"\"\r\n}";
+        let value_start = input.find("\"old\"").unwrap();
+        let value_end = value_start + "\"old\"".len();
+
+        let output = set_str(input, &LAUNCH_ARGS, "new").unwrap().unwrap();
+
+        assert_eq!(&output[..value_start], &input[..value_start]);
+        assert_eq!(
+            &output[value_start..value_start + "\"new\"".len()],
+            "\"new\""
+        );
+        assert_eq!(
+            &output[value_start + "\"new\"".len()..],
+            &input[value_end..]
+        );
+    }
+
+    #[test]
+    fn inserts_as_the_first_member() {
+        let input = CONFIG.replace(
+            "\"AdditionalLaunchArguments\": \"--tank_Console\",\n            ",
+            "",
+        );
+
+        let output = set_str(&input, &LAUNCH_ARGS, "--tank_Console")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(output, CONFIG);
+    }
+
+    #[test]
+    fn inserts_into_an_empty_object() {
+        let input = "{\n    \"Games\": {\n        \"prometheus\": {}\n    }\n}\n";
+
+        let output = set_str(input, &LAUNCH_ARGS, "--tank_Console")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            output,
+            "{\n    \"Games\": {\n        \"prometheus\": {\"AdditionalLaunchArguments\": \"--tank_Console\"}\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn follows_nested_paths() {
+        let output = set_str(CONFIG, &["Client", "Sound", "Enabled"], "false")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            output,
+            CONFIG.replace("\"Enabled\": \"true\"", "\"Enabled\": \"false\"")
+        );
+    }
+
+    #[test]
+    fn returns_none_for_a_missing_parent() {
+        assert!(
+            set_str(CONFIG, &["Games", "pro", "AdditionalLaunchArguments"], "")
+                .unwrap()
+                .is_none()
+        );
+        assert!(set_str(
+            CONFIG,
+            &["Games", "prometheus", "LastActioned", "Value"],
+            ""
+        )
+        .unwrap()
+        .is_none());
+    }
+
+    #[test]
+    fn keeps_the_byte_order_mark() {
+        let input = format!("\u{feff}{}", CONFIG);
+
+        let output = set_str(&input, &LAUNCH_ARGS, "").unwrap().unwrap();
+
+        assert_eq!(
+            output,
+            format!("\u{feff}{}", CONFIG.replace("\"--tank_Console\"", "\"\""))
+        );
+    }
+
+    #[test]
+    fn matches_escaped_keys() {
+        let input = r#"{"a\"b": "1", "\u0063": "2"}"#;
+
+        assert_eq!(
+            set_str(input, &["a\"b"], "3").unwrap().unwrap(),
+            r#"{"a\"b": "3", "\u0063": "2"}"#
+        );
+        assert_eq!(
+            set_str(input, &["c"], "3").unwrap().unwrap(),
+            r#"{"a\"b": "1", "\u0063": "3"}"#
+        );
+    }
+
+    #[test]
+    fn escapes_written_values() {
+        let output = set_str(r#"{"a": ""}"#, &["a"], "say \"hi\" C:\\")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(output, r#"{"a": "say \"hi\" C:\\"}"#);
+    }
+
+    #[test]
+    fn reports_malformed_input() {
+        let error = set_str("{\n  \"a\": \"1\"\n  \"b\": \"2\"\n}", &["b"], "").unwrap_err();
+
+        assert_eq!((error.line, error.column), (3, 3));
+        assert!(set_str("{\"a\": \"1", &["a"], "").is_err());
+    }
+}
@@ -0,0 +1,319 @@
+use std::fmt;
+
+// Launch argument strings
+//
+// Every token keeps its original text and the whitespace before it, so arguments that are
+// not touched serialize back exactly as the user wrote them.
+
+/// Parsed launch arguments, e.g. `--tank_Console -exec "my cfg" --lobbyMap=0x0800000000000E1F`.
+#[derive(Debug, Clone, Default)]
+pub struct LaunchArgs {
+    tokens: Vec<Token>,
+    trailing: String,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    leading: String,
+    raw: String,
+    value: String,
+}
+
+impl Token {
+    fn new(leading: &str, value: &str) -> Self {
+        Token {
+            leading: leading.to_string(),
+            raw: quote(value),
+            value: value.to_string(),
+        }
+    }
+
+    /// Check if the token is the given flag, with or without a `=value`.
+    fn is(&self, name: &str) -> bool {
+        self.value == name || self.value_of(name).is_some()
+    }
+
+    fn value_of(&self, name: &str) -> Option<&str> {
+        self.value
+            .strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix('='))
+    }
+}
+
+impl LaunchArgs {
+    /// Split launch arguments on whitespace outside of double quotes.
+    pub fn parse(input: &str) -> LaunchArgs {
+        let mut tokens = vec![];
+        let mut chars = input.char_indices().peekable();
+        let mut leading_start = 0;
+
+        while let Some(&(start, char)) = chars.peek() {
+            if char.is_whitespace() {
+                chars.next();
+                continue;
+            }
+
+            let mut value = String::new();
+            let mut end = input.len();
+            let mut in_quotes = false;
+            while let Some((index, char)) = chars.next() {
+                match char {
+                    '\\' if in_quotes && chars.peek().is_some_and(|&(_, next)| next == '"') => {
+                        value.push('"');
+                        chars.next();
+                    }
+                    '"' => in_quotes = !in_quotes,
+                    char if char.is_whitespace() && !in_quotes => {
+                        end = index;
+                        break;
+                    }
+                    char => value.push(char),
+                }
+            }
+
+            tokens.push(Token {
+                leading: input[leading_start..start].to_string(),
+                raw: input[start..end].to_string(),
+                value,
+            });
+            leading_start = end;
+        }
+
+        LaunchArgs {
+            tokens,
+            trailing: input[leading_start..].to_string(),
+        }
+    }
+
+    /// Get the value of the first `name=value` argument.
+    pub fn get_value(&self, name: &str) -> Option<&str> {
+        self.tokens.iter().find_map(|token| token.value_of(name))
+    }
+
+    /// Check if the flag is present, with or without a value.
+    pub fn has(&self, name: &str) -> bool {
+        self.tokens.iter().any(|token| token.is(name))
+    }
+
+    /// Get the byte offset in the parsed input just past the first argument equal to `value`,
+    /// including its closing quote.
+    pub fn find_end(&self, value: &str) -> Option<usize> {
+        let mut end = 0;
+        for token in &self.tokens {
+            end += token.leading.len() + token.raw.len();
+            if token.value == value {
+                return Some(end);
+            }
+        }
+        None
+    }
+
+    /// Set `name=value`, replacing the first occurrence of the flag and removing duplicates.
+    pub fn set_value(&mut self, name: &str, value: &str) {
+        self.set_token(name, &format!("{}={}", name, value));
+    }
+
+    /// Set a flag without a value, replacing the first occurrence and removing duplicates.
+    pub fn set_flag(&mut self, name: &str) {
+        self.set_token(name, name);
+    }
+
+    /// Remove every occurrence of the flag.
+    pub fn remove(&mut self, name: &str) {
+        while let Some(index) = self.tokens.iter().position(|token| token.is(name)) {
+            self.remove_at(index);
+        }
+    }
+
+    fn set_token(&mut self, name: &str, value: &str) {
+        match self.tokens.iter().position(|token| token.is(name)) {
+            Some(index) => {
+                if self.tokens[index].value != value {
+                    let leading = self.tokens[index].leading.clone();
+                    self.tokens[index] = Token::new(&leading, value);
+                }
+                while let Some(duplicate) = self
+                    .tokens
+                    .iter()
+                    .skip(index + 1)
+                    .position(|token| token.is(name))
+                {
+                    self.remove_at(index + 1 + duplicate);
+                }
+            }
+            None => self.push(value),
+        }
+    }
+
+    fn push(&mut self, value: &str) {
+        let leading = if self.tokens.is_empty() { "" } else { " " };
+        self.tokens.push(Token::new(leading, value));
+    }
+
+    fn remove_at(&mut self, index: usize) {
+        let token = self.tokens.remove(index);
+        // Keep the whitespace before the first argument
+        if index == 0 {
+            if let Some(next) = self.tokens.first_mut() {
+                next.leading = token.leading;
+            }
+        }
+        if self.tokens.is_empty() {
+            self.trailing.clear();
+        }
+    }
+}
+
+impl fmt::Display for LaunchArgs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in &self.tokens {
+            write!(f, "{}{}", token.leading, token.raw)?;
+        }
+        write!(f, "{}", self.trailing)
+    }
+}
+
+/// Quote the value part of an argument if it contains whitespace or quotes.
+fn quote(value: &str) -> String {
+    if !value.contains(|char: char| char.is_whitespace() || char == '"') {
+        return value.to_string();
+    }
+
+    let (prefix, value) = match value.split_once('=') {
+        Some((name, value)) => (format!("{}=", name), value),
+        None => (String::new(), value),
+    };
+    format!("{}\"{}\"", prefix, value.replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(input: &str, edit: impl FnOnce(&mut LaunchArgs)) -> String {
+        let mut launch_args = LaunchArgs::parse(input);
+        edit(&mut launch_args);
+        launch_args.to_string()
+    }
+
+    #[test]
+    fn round_trips_untouched_arguments() {
+        let input = "  --tank_Console\t-exec \"my cfg\"   --lobbyMap=0x0800000000000E1F  ";
+
+        assert_eq!(LaunchArgs::parse(input).to_string(), input);
+    }
+
+    #[test]
+    fn sets_a_value_in_place() {
+        assert_eq!(
+            edit("--tank_Console   --lobbyMap=0x1\t-foo ", |args| {
+                args.set_value("--lobbyMap", "0x2")
+            }),
+            "--tank_Console   --lobbyMap=0x2\t-foo "
+        );
+    }
+
+    #[test]
+    fn appends_missing_arguments() {
+        assert_eq!(
+            edit("--tank_Console", |args| args.set_value("--lobbyMap", "0x2")),
+            "--tank_Console --lobbyMap=0x2"
+        );
+        assert_eq!(
+            edit("", |args| args.set_flag("--tank_Console")),
+            "--tank_Console"
+        );
+    }
+
+    #[test]
+    fn removes_arguments_and_their_whitespace() {
+        assert_eq!(
+            edit("--a  --lobbyMap=0x1 --b", |args| args.remove("--lobbyMap")),
+            "--a --b"
+        );
+        // The whitespace before the first argument belongs to the arguments
+        assert_eq!(
+            edit("  --lobbyMap=0x1   --tank_Console  ", |args| {
+                args.remove("--lobbyMap")
+            }),
+            "  --tank_Console  "
+        );
+        assert_eq!(
+            edit("--lobbyMap=0x1 ", |args| args.remove("--lobbyMap")),
+            ""
+        );
+    }
+
+    #[test]
+    fn removes_duplicates() {
+        let input = "--lobbyMap=0x1 --a --lobbyMap=0x2 --lobbyMap";
+
+        assert_eq!(
+            edit(input, |args| args.set_value("--lobbyMap", "0x3")),
+            "--lobbyMap=0x3 --a"
+        );
+        assert_eq!(edit(input, |args| args.remove("--lobbyMap")), "--a");
+        assert_eq!(
+            edit("--tank_Console=1 --tank_Console", |args| {
+                args.set_flag("--tank_Console")
+            }),
+            "--tank_Console"
+        );
+    }
+
+    #[test]
+    fn matches_whole_flags() {
+        let launch_args = LaunchArgs::parse("--tank_ConsoleX --lobbyMapX=0x1");
+
+        assert!(!launch_args.has("--tank_Console"));
+        assert_eq!(launch_args.get_value("--lobbyMap"), None);
+    }
+
+    #[test]
+    fn reads_quoted_values() {
+        let launch_args = LaunchArgs::parse("-exec \"my cfg\" --name=\"a b\"");
+
+        assert!(launch_args.has("-exec"));
+        assert_eq!(launch_args.get_value("--name"), Some("a b"));
+    }
+
+    #[test]
+    fn quotes_written_values() {
+        assert_eq!(
+            edit("--a", |args| args.set_value("--name", "c d")),
+            "--a --name=\"c d\""
+        );
+        assert_eq!(
+            edit("--a", |args| args.set_value("--name", "say \"hi\"")),
+            "--a --name=\"say \\\"hi\\\"\""
+        );
+    }
+
+    #[test]
+    fn reads_escaped_quotes() {
+        let launch_args = LaunchArgs::parse("--name=\"a \\\"b\\\" c\" --d");
+
+        assert_eq!(launch_args.get_value("--name"), Some("a \"b\" c"));
+        assert!(launch_args.has("--d"));
+
+        let written = edit("", |args| args.set_value("--name", "a \"b\" c"));
+        assert_eq!(
+            LaunchArgs::parse(&written).get_value("--name"),
+            Some("a \"b\" c")
+        );
+    }
+
+    #[test]
+    fn keeps_unterminated_quotes_to_the_end() {
+        let input = "--a \"unterminated value --b";
+        let launch_args = LaunchArgs::parse(input);
+
+        assert!(launch_args.has("--a"));
+        assert!(!launch_args.has("--b"));
+        assert_eq!(launch_args.to_string(), input);
+        assert_eq!(
+            edit(input, |args| args.remove("--a")),
+            "\"unterminated value --b"
+        );
+    }
+}
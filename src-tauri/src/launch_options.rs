@@ -0,0 +1,137 @@
+use crate::helpers::Error;
+use crate::launch_args::LaunchArgs;
+
+/// The kind of value a launch option takes.
+#[derive(serde::Serialize, Clone, Copy, Debug)]
+#[serde(tag = "type")]
+pub enum OptionKind {
+    /// A flag without a value, e.g. `--tank_Console`.
+    Flag,
+    /// A whole number within a range, e.g. `--tank_WorkerThreadCount=4`.
+    Integer { min: i64, max: i64 },
+    /// A hexadecimal id, e.g. `--lobbyMap=0x0800000000000E1F`.
+    HexId,
+}
+
+#[derive(serde::Serialize, Debug)]
+pub struct LaunchOption {
+    pub name: &'static str,
+    pub kind: OptionKind,
+    pub description: &'static str,
+}
+
+pub const LOBBY_MAP: &str = "--lobbyMap";
+pub const TANK_CONSOLE: &str = "--tank_Console";
+
+/// Launch options known to work, each with the source documenting it.
+///
+/// Only add flags whose value type and range are documented, e.g. in the community list of
+/// Overwatch command line arguments credited in the README.
+pub static LAUNCH_OPTIONS: &[LaunchOption] = &[
+    // Takes the hexadecimal id of a main menu background, see SkyBorik's "How to change
+    // Main Menu background" guide credited in the README
+    LaunchOption {
+        name: LOBBY_MAP,
+        kind: OptionKind::HexId,
+        description: "Sets the main menu background.",
+    },
+    // Takes no value, see the community list of Overwatch command line arguments credited
+    // in the README
+    LaunchOption {
+        name: TANK_CONSOLE,
+        kind: OptionKind::Flag,
+        description: "Enables the debug console, opened with the ~ key.",
+    },
+    // Takes a thread count, see Toyz's "Overwatch command line arguments" gist credited in
+    // the README: https://gist.github.com/Toyz/30e6fd504c713511f67f1a607025b0bc
+    LaunchOption {
+        name: "--tank_WorkerThreadCount",
+        kind: OptionKind::Integer { min: 1, max: 64 },
+        description: "Sets the number of worker threads the game uses.",
+    },
+];
+
+pub fn get_launch_options() -> &'static [LaunchOption] {
+    LAUNCH_OPTIONS
+}
+
+pub fn find_launch_option(name: &str) -> Option<&'static LaunchOption> {
+    LAUNCH_OPTIONS.iter().find(|option| option.name == name)
+}
+
+impl LaunchOption {
+    /// Check that a value is valid for this option. Flags take no value.
+    pub fn validate(&self, value: Option<&str>) -> Result<(), Error> {
+        let value = match (self.kind, value) {
+            (OptionKind::Flag, None) => return Ok(()),
+            (OptionKind::Flag, Some(_)) => {
+                return Err(Error::Custom(format!(
+                    "Launch option [[{}]] does not take a value",
+                    self.name
+                )));
+            }
+            (_, None) => {
+                return Err(Error::Custom(format!(
+                    "Launch option [[{}]] requires a value",
+                    self.name
+                )));
+            }
+            (_, Some(value)) => value,
+        };
+
+        let is_valid = match self.kind {
+            OptionKind::Flag => true,
+            OptionKind::Integer { min, max } => value
+                .parse::<i64>()
+                .is_ok_and(|value| (min..=max).contains(&value)),
+            OptionKind::HexId => value.strip_prefix("0x").is_some_and(|digits| {
+                !digits.is_empty() && digits.chars().all(|char| char.is_ascii_hexdigit())
+            }),
+        };
+        if !is_valid {
+            return Err(Error::Custom(format!(
+                "Invalid value [[{}]] for launch option [[{}]], expected {}",
+                value,
+                self.name,
+                self.kind.describe()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Check if this option is present in the launch arguments.
+    pub fn is_set(&self, launch_args: &LaunchArgs) -> bool {
+        launch_args.has(self.name)
+    }
+
+    /// Get the value of this option. Flags never have a value.
+    pub fn get_value<'a>(&self, launch_args: &'a LaunchArgs) -> Option<&'a str> {
+        match self.kind {
+            OptionKind::Flag => None,
+            _ => launch_args.get_value(self.name),
+        }
+    }
+
+    /// Set this option, or remove it if `enabled` is false.
+    pub fn set(&self, launch_args: &mut LaunchArgs, enabled: bool, value: Option<&str>) {
+        match (enabled, self.kind, value) {
+            (false, _, _) => launch_args.remove(self.name),
+            (true, OptionKind::Flag, _) => launch_args.set_flag(self.name),
+            (true, _, Some(value)) => launch_args.set_value(self.name, value),
+            (true, _, None) => launch_args.remove(self.name),
+        }
+    }
+}
+
+impl OptionKind {
+    fn describe(&self) -> String {
+        match self {
+            OptionKind::Flag => "no value".to_string(),
+            OptionKind::Integer { min, max } => {
+                format!("a whole number from {} to {}", min, max)
+            }
+            OptionKind::HexId => "a hexadecimal id starting with 0x".to_string(),
+        }
+    }
+}
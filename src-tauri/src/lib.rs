@@ -1,60 +1,648 @@
 mod backgrounds;
 mod config;
 mod helpers;
+mod journal;
+mod json_edit;
+mod launch_args;
+mod launch_options;
 mod platforms;
+mod process;
+mod vdf;
 
-use config::{ErrorKey, SetupError};
+use config::{ErrorKey, SetupError, Target};
 use helpers::Error;
 use platforms::{battle_net, steam};
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
 use tauri::AppHandle;
+use tauri::Emitter;
 use tauri::Manager;
 
-#[tauri::command]
-fn get_launch_config(handle: AppHandle) -> Result<String, Error> {
-    let mut config = config::read_config(&handle)?;
-    let mut battle_net_shared: Option<config::SharedConfig> = None;
-    let mut steam_shared: Option<config::SharedConfig> = None;
+/// Held while reading and writing the configuration.
+static CONFIG_LOCK: Mutex<()> = Mutex::new(());
+/// Held while writing to client config files, which may close and restart the clients.
+/// Always taken before `CONFIG_LOCK` when both are needed.
+static APPLY_LOCK: Mutex<()> = Mutex::new(());
+static PENDING_CHANGES_INTERVAL: Duration = Duration::from_secs(2);
 
-    if !config.battle_net.enabled && !config.steam.enabled {
-        config.is_setup = false;
+/// Update the state of every target and summarize it in the shared configuration.
+fn update_shared_config(config: &mut config::Config) -> Result<(), Error> {
+    let mut targets: Vec<config::TargetState> = vec![];
+
+    if config.battle_net.enabled {
+        battle_net::record_runners(config);
+        targets.push(battle_net::update_config(config)?);
     }
 
-    if config.is_setup {
-        // TODO: Show user a warning if the backup file exists
+    if config.steam.enabled && !config.steam.in_setup {
+        targets.extend(steam::update_config(config)?);
+    }
 
-        if config.battle_net.enabled {
-            battle_net_shared = battle_net::update_config(&config)?;
+    // The shared background is only set when every target agrees
+    let mut shared = config::SharedConfig::default();
+    if let Some(first) = targets.first() {
+        if targets
+            .iter()
+            .all(|state| state.background.current == first.background.current)
+        {
+            shared.background.current = first.background.current.clone();
+        }
+        shared.background.is_outdated = targets.iter().any(|state| state.background.is_outdated);
+        shared.background.unknown = targets
+            .iter()
+            .find_map(|state| state.background.unknown.clone());
+        shared.background.retired = targets
+            .iter()
+            .find_map(|state| state.background.retired.clone());
+        shared.additional.console_enabled =
+            targets.iter().all(|state| state.additional.console_enabled);
+    }
+
+    config.shared = shared;
+    config.targets = targets;
+
+    Ok(())
+}
+
+/// Get all targets currently managed by OverBuddy.
+fn get_targets(config: &config::Config) -> Vec<Target> {
+    let mut targets: Vec<Target> = vec![];
+
+    if config.battle_net.enabled {
+        targets.push(Target::BattleNet);
+    }
+
+    if config.steam.enabled {
+        if let Some(profiles) = &config.steam.profiles {
+            targets.extend(
+                profiles
+                    .iter()
+                    .filter(|profile| profile.enabled && profile.has_overwatch)
+                    .map(|profile| Target::Steam(profile.id.clone())),
+            );
+        }
+    }
+
+    targets
+}
+
+/// Set the launch arguments of the given targets, reporting failures for each platform.
+///
+/// Every target is checked before anything is written, and if writing to one target fails,
+/// the files already written are restored so the change applies to all targets or none.
+fn apply_to_targets<F, P>(
+    config: &config::Config,
+    targets: &[Target],
+    params: P,
+    generate_launch_args: F,
+    action: &str,
+) -> Result<(), Error>
+where
+    F: Fn(Option<&str>, P) -> String + Copy,
+    P: Clone,
+{
+    let mut battle_net_error: Option<Error> = None;
+    let mut steam_error: Option<Error> = None;
+
+    // Step 1: Check every target
+    let has_battle_net = targets.contains(&Target::BattleNet);
+    if has_battle_net && !config.battle_net.enabled {
+        battle_net_error = Some(Error::Custom("Battle.net is not enabled".into()));
+    }
+
+    let accounts: Vec<&str> = targets
+        .iter()
+        .filter_map(|target| match target {
+            Target::Steam(id) => Some(id.as_str()),
+            Target::BattleNet => None,
+        })
+        .collect();
+    let available = get_targets(config);
+    if let Some(id) = accounts
+        .iter()
+        .find(|id| !available.contains(&Target::Steam(id.to_string())))
+    {
+        steam_error = Some(Error::Custom(format!(
+            "Steam account [[{}]] is not enabled or does not have Overwatch",
+            id
+        )));
+    }
+
+    if battle_net_error.is_none() && steam_error.is_none() {
+        // Step 2: Remember the files of every target, so a failed write can be undone
+        let battle_net_originals = if has_battle_net {
+            helpers::read_originals(&battle_net::get_config_files(config))?
+        } else {
+            vec![]
+        };
+        let steam_originals =
+            helpers::read_originals(&steam::get_accounts_files(config, &accounts))?;
+
+        // Step 3: Write each target, stopping at the first failure
+        if has_battle_net {
+            if let Err(error) =
+                battle_net::set_launch_args(config, params.clone(), generate_launch_args)
+            {
+                battle_net_error = Some(error);
+            }
+        }
+        if battle_net_error.is_none() && !accounts.is_empty() {
+            if let Err(error) =
+                steam::set_accounts_launch_args(config, &accounts, params, generate_launch_args)
+            {
+                steam_error = Some(error);
+            }
+        }
+
+        // Step 4: Undo the change on every target if it failed on one
+        if battle_net_error.is_some() || steam_error.is_some() {
+            let undo_result = battle_net::restore_files(config, &battle_net_originals)
+                .and(steam::restore_files(config, &steam_originals));
+            let error = battle_net_error.as_ref().or(steam_error.as_ref());
+            if let (Err(undo_error), Some(error)) = (undo_result, error) {
+                return Err(Error::Custom(format!(
+                    "Failed to {}: {}\nAlso failed to undo the change on the other targets: {}",
+                    action, error, undo_error
+                )));
+            }
         }
+    }
+
+    match (battle_net_error, steam_error) {
+        (Some(battle_net_error), Some(steam_error)) => Err(Error::Custom(format!(
+            "Failed to {} on Battle.net: {}\nAlso failed to {} on Steam: {}",
+            action, battle_net_error, action, steam_error,
+        ))),
+        (Some(error), None) | (None, Some(error)) => {
+            Err(Error::Custom(format!("Failed to {}: {}", action, error)))
+        }
+        (None, None) => Ok(()),
+    }
+}
 
-        if config.steam.enabled && !config.steam.in_setup {
-            steam_shared = steam::update_config(&mut config)?;
+/// Apply a launch argument change to the given targets.
+fn apply_change(
+    config: &config::Config,
+    targets: &[Target],
+    change: &config::LaunchChange,
+) -> Result<(), Error> {
+    match change {
+        config::LaunchChange::Background(Some(id)) => apply_to_targets(
+            config,
+            targets,
+            Some(id.as_str()),
+            helpers::generate_background_launch_args,
+            "apply background",
+        ),
+        config::LaunchChange::Background(None) => apply_to_targets(
+            config,
+            targets,
+            None,
+            helpers::generate_background_launch_args,
+            "reset background",
+        ),
+        config::LaunchChange::Console(enabled) => apply_to_targets(
+            config,
+            targets,
+            *enabled,
+            helpers::generate_console_launch_args,
+            "apply debug console",
+        ),
+        config::LaunchChange::LaunchOption {
+            name,
+            enabled,
+            value,
+        } => {
+            let option = launch_options::find_launch_option(name)
+                .ok_or_else(|| Error::Custom(format!("Unknown launch option [[{}]]", name)))?;
+            apply_to_targets(
+                config,
+                targets,
+                (option, *enabled, value.as_deref()),
+                helpers::generate_launch_option_args,
+                &format!("apply launch option {}", name),
+            )
         }
+        config::LaunchChange::Preset(preset) => apply_to_targets(
+            config,
+            targets,
+            preset,
+            helpers::generate_preset_launch_args,
+            &format!("apply preset {}", preset.name),
+        ),
+    }
+}
+
+/// Queue changes for targets whose client is running when changes are deferred, or always
+/// if `always_defer` is set, and get the changes to apply now.
+fn queue_changes(
+    handle: &AppHandle,
+    config: &mut config::Config,
+    always_defer: bool,
+    changes: Vec<(Vec<Target>, config::LaunchChange)>,
+) -> Vec<(Vec<Target>, config::LaunchChange)> {
+    let mut immediate: Vec<(Vec<Target>, config::LaunchChange)> = vec![];
+    let mut queue_changed = false;
+    for (targets, change) in changes {
+        let (battle_net_targets, steam_targets): (Vec<Target>, Vec<Target>) = targets
+            .into_iter()
+            .partition(|target| *target == Target::BattleNet);
+
+        let mut apply_now: Vec<Target> = vec![];
+        for (targets, is_running) in [
+            (battle_net_targets, battle_net::is_running as fn() -> bool),
+            (steam_targets, steam::is_running),
+        ] {
+            if targets.is_empty() {
+                continue;
+            }
 
-        // Merge shared config
-        if let (Some(battle_net_shared), Some(steam_shared)) = (&battle_net_shared, &steam_shared) {
-            if battle_net_shared.background.current == steam_shared.background.current {
-                config.shared.background.current = battle_net_shared.background.current.clone();
+            // Drop older changes this change overrides to the same targets
+            for pending in config.pending.iter_mut() {
+                if change.replaces(&pending.change)
+                    && pending
+                        .targets
+                        .iter()
+                        .any(|target| targets.contains(target))
+                {
+                    pending.targets.retain(|target| !targets.contains(target));
+                    queue_changed = true;
+                }
+            }
+            config.pending.retain(|pending| !pending.targets.is_empty());
+
+            if (config.defer_changes || always_defer) && is_running() {
+                let id = config
+                    .pending
+                    .iter()
+                    .map(|pending| pending.id)
+                    .max()
+                    .unwrap_or(0)
+                    + 1;
+                config.pending.push(config::PendingChange {
+                    id,
+                    targets,
+                    change: change.clone(),
+                });
+                queue_changed = true;
             } else {
-                config.shared.background.current = None;
+                apply_now.extend(targets);
             }
-            config.shared.background.is_outdated =
-                battle_net_shared.background.is_outdated || steam_shared.background.is_outdated;
-
-            config.shared.additional.console_enabled = battle_net_shared.additional.console_enabled
-                && steam_shared.additional.console_enabled;
-        } else if let Some(shared) = battle_net_shared.as_ref().or(steam_shared.as_ref()) {
-            config.shared.background.current = shared.background.current.clone();
-            config.shared.background.is_outdated = shared.background.is_outdated;
-            config.shared.additional.console_enabled = shared.additional.console_enabled;
-        } else {
-            config.shared.background.current = None;
-            config.shared.background.is_outdated = false;
-            config.shared.additional.console_enabled = false;
         }
+
+        if !apply_now.is_empty() {
+            immediate.push((apply_now, change));
+        }
+    }
+
+    if queue_changed {
+        handle.emit("pending_changes", &config.pending).ok();
+    }
+    immediate
+}
+
+/// Apply launch argument changes without holding the configuration lock while clients are
+/// closed and restarted.
+///
+/// `prepare` gets each change and its targets from the configuration, and may update it.
+/// When changes are deferred, targets whose client is running get the change queued instead.
+fn change_launch_args<F>(handle: &AppHandle, prepare: F) -> Result<config::Config, Error>
+where
+    F: FnOnce(&mut config::Config) -> Result<Vec<(Vec<Target>, config::LaunchChange)>, Error>,
+{
+    change_launch_args_with(handle, false, prepare)
+}
+
+/// Apply launch argument changes, queueing them for every running client if `always_defer`
+/// is set, regardless of the user's setting.
+fn change_launch_args_with<F>(
+    handle: &AppHandle,
+    always_defer: bool,
+    prepare: F,
+) -> Result<config::Config, Error>
+where
+    F: FnOnce(&mut config::Config) -> Result<Vec<(Vec<Target>, config::LaunchChange)>, Error>,
+{
+    // Step 1: Queue the changes that wait for a client to exit
+    let (config, immediate) = {
+        let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut config = config::read_config(handle)?;
+        let changes = prepare(&mut config)?;
+        if changes.is_empty() {
+            return Ok(config);
+        }
+        let immediate = queue_changes(handle, &mut config, always_defer, changes);
+        config::write_config(handle, &config)?;
+        (config, immediate)
+    };
+
+    // Step 2: Apply the rest
+    let result = {
+        let _lock = APPLY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        immediate
+            .iter()
+            .try_for_each(|(targets, change)| apply_change(&config, targets, change))
+    };
+    emit_restart_offers(handle);
+
+    // Step 3: Record the new state of the targets, even if applying failed
+    let config = record_target_states(handle);
+    result?;
+    config
+}
+
+/// Read the state of every target into the configuration.
+fn record_target_states(handle: &AppHandle) -> Result<config::Config, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(handle)?;
+    update_shared_config(&mut config)?;
+    config::write_config(handle, &config)?;
+
+    Ok(config)
+}
+
+/// Check that the clients a command writes to directly are closed, if changes are deferred.
+/// These writes cannot be queued, and closing a client would ignore the user's setting.
+fn check_clients_closed(
+    config: &config::Config,
+    battle_net: bool,
+    steam: bool,
+) -> Result<(), Error> {
+    if !config.defer_changes {
+        return Ok(());
+    }
+
+    for (name, is_affected, is_running) in [
+        (
+            "Battle.net",
+            battle_net,
+            battle_net::is_running as fn() -> bool,
+        ),
+        ("Steam", steam, steam::is_running),
+    ] {
+        if is_affected && is_running() {
+            return Err(Error::Custom(format!(
+                "Please close [[{}]] and try again. Changes wait for it to exit, but this one cannot be queued",
+                name
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Take both locks, for commands that write to client config files directly.
+fn lock_config_and_clients() -> (MutexGuard<'static, ()>, MutexGuard<'static, ()>) {
+    let apply_lock = APPLY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let config_lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    (apply_lock, config_lock)
+}
+
+/// Run a command on a blocking worker thread. Closing a client waits for it to exit, which
+/// would otherwise freeze the window.
+async fn run_blocking<F>(command: F) -> Result<String, Error>
+where
+    F: FnOnce() -> Result<String, Error> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(command)
+        .await
+        .map_err(|e| Error::Custom(format!("Failed to run the command: {}", e)))?
+}
+
+/// Let the frontend know which clients wait for the user to restart them.
+fn emit_restart_offers(handle: &AppHandle) {
+    let platforms = process::get_restart_offers();
+    if !platforms.is_empty() {
+        handle.emit("restart_offers", platforms).ok();
+    }
+}
+
+/// Pick the next background from the rotation pool, avoiding repeats until every
+/// background in the pool was used.
+fn rotate_background(handle: &AppHandle, trigger: config::RotationTrigger) -> Result<(), Error> {
+    let mut rotated: Option<String> = None;
+    // Never close a client to rotate, wait for it to exit instead
+    change_launch_args_with(handle, true, |config| {
+        let is_due = config
+            .rotation
+            .as_ref()
+            .is_some_and(|rotation| rotation.trigger == trigger);
+        if !config.is_setup || !is_due {
+            return Ok(vec![]);
+        }
+
+        // The stored backgrounds may be stale if the clients changed them
+        update_shared_config(config)?;
+        let Some(rotation) = config.rotation.as_mut() else {
+            return Ok(vec![]);
+        };
+
+        // Step 1: Get the backgrounds that were not used yet, or set on any target
+        let pool = backgrounds::get_rotation_pool(&rotation.pool, &config.custom_backgrounds);
+        let current: Vec<String> = config
+            .targets
+            .iter()
+            .filter_map(|state| state.background.current.clone())
+            .collect();
+        let mut candidates =
+            backgrounds::pick_rotation_candidates(&pool, &mut rotation.used, &current);
+        if candidates.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // Step 2: Pick a random one and remember it so it is not picked again this round
+        let id = candidates.swap_remove(fastrand::usize(..candidates.len()));
+        rotation.used.push(id.clone());
+        rotated = Some(id.clone());
+
+        // Step 3: Set it through the same path as choosing a background
+        Ok(vec![(
+            get_targets(config),
+            config::LaunchChange::Background(Some(id)),
+        )])
+    })?;
+
+    if let Some(id) = rotated {
+        handle.emit("background_rotated", id).ok();
+    }
+
+    Ok(())
+}
+
+/// Record the backgrounds the user has already seen if the config predates seen ids.
+fn migrate_seen_backgrounds(handle: &AppHandle) -> Result<(), Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(handle)?;
+    if config.seen_backgrounds.is_some() {
+        return Ok(());
+    }
+
+    // A config that finished setup was written by an earlier version
+    config.seen_backgrounds = Some(backgrounds::get_initial_seen_ids(config.is_setup));
+    config::write_config(handle, &config)
+}
+
+/// Rotate the background, letting the frontend know if it failed.
+fn rotate_background_or_emit(handle: &AppHandle, trigger: config::RotationTrigger) {
+    if let Err(error) = rotate_background(handle, trigger) {
+        handle
+            .emit("background_rotation_failed", error.to_string())
+            .ok();
+    }
+}
+
+/// Watch for clients to exit and apply the changes waiting for them. Also rotates the
+/// background when Overwatch exits.
+fn watch_pending_changes(handle: AppHandle) {
+    let mut overwatch_was_running = false;
+    loop {
+        thread::sleep(PENDING_CHANGES_INTERVAL);
+
+        // Only look for the game when a rotation waits for it to exit
+        let watch_game_exit = {
+            let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            config::read_config(&handle).is_ok_and(|config| {
+                config
+                    .rotation
+                    .is_some_and(|rotation| rotation.trigger == config::RotationTrigger::GameExit)
+            })
+        };
+        let overwatch_running = watch_game_exit && process::is_overwatch_running();
+        if overwatch_was_running && !overwatch_running {
+            rotate_background_or_emit(&handle, config::RotationTrigger::GameExit);
+        }
+        overwatch_was_running = overwatch_running;
+
+        apply_pending_changes(&handle).ok();
+    }
+}
+
+/// Apply the queued changes whose client is no longer running.
+fn apply_pending_changes(handle: &AppHandle) -> Result<(), Error> {
+    let _apply_lock = APPLY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    // Step 1: Take the changes whose client exited off the queue
+    let (config, ready) = {
+        let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut config = config::read_config(handle)?;
+        if config.pending.is_empty() {
+            return Ok(());
+        }
+
+        let battle_net_running = battle_net::is_running();
+        let steam_running = steam::is_running();
+        let (ready, remaining): (Vec<config::PendingChange>, Vec<config::PendingChange>) =
+            std::mem::take(&mut config.pending)
+                .into_iter()
+                .partition(|pending| match pending.targets.first() {
+                    Some(Target::BattleNet) => !battle_net_running,
+                    Some(Target::Steam(_)) => !steam_running,
+                    None => true,
+                });
+        if ready.is_empty() {
+            return Ok(());
+        }
+
+        config.pending = remaining;
+        config::write_config(handle, &config)?;
+        (config, ready)
+    };
+    handle.emit("pending_changes", &config.pending).ok();
+
+    // Step 2: Apply them without holding the configuration lock
+    for pending in ready {
+        if let Err(error) = apply_change(&config, &pending.targets, &pending.change) {
+            handle
+                .emit(
+                    "pending_change_failed",
+                    serde_json::json!({ "id": pending.id, "error": error.to_string() }),
+                )
+                .ok();
+        }
+    }
+    emit_restart_offers(handle);
+
+    // Step 3: Record the new state of the targets
+    record_target_states(handle).map(|_| ())
+}
+
+/// Get every config file OverBuddy writes to.
+fn get_config_files(config: &config::Config) -> Vec<&str> {
+    let mut files = battle_net::get_config_files(config);
+    if let Some(configs) = &config.steam.configs {
+        files.extend(
+            configs
+                .iter()
+                .map(|steam_config| steam_config.file.as_str()),
+        );
+    }
+
+    files
+}
+
+/// Check that a preset has a unique name and only sets known launch options.
+fn validate_preset(config: &config::Config, preset: &config::Preset) -> Result<(), Error> {
+    if preset.name.trim().is_empty() {
+        return Err(Error::Custom("Preset name cannot be empty".to_string()));
+    }
+    if config
+        .presets
+        .iter()
+        .any(|other| other.name.eq_ignore_ascii_case(&preset.name))
+    {
+        return Err(Error::Custom(format!(
+            "A preset named [[{}]] already exists",
+            preset.name
+        )));
+    }
+
+    if let Some(id) = &preset.background {
+        backgrounds::validate_background_id(id, &config.custom_backgrounds)?;
+    }
+
+    for (index, preset_option) in preset.options.iter().enumerate() {
+        let option = launch_options::find_launch_option(&preset_option.name).ok_or_else(|| {
+            Error::Custom(format!("Unknown launch option [[{}]]", preset_option.name))
+        })?;
+        // The background and console have their own preset fields
+        if option.name == launch_options::LOBBY_MAP || option.name == launch_options::TANK_CONSOLE {
+            return Err(Error::Custom(format!(
+                "Launch option [[{}]] cannot be added to a preset",
+                option.name
+            )));
+        }
+        if preset.options[..index]
+            .iter()
+            .any(|other| other.name == preset_option.name)
+        {
+            return Err(Error::Custom(format!(
+                "Launch option [[{}]] is set more than once",
+                option.name
+            )));
+        }
+        if preset_option.enabled {
+            option.validate(preset_option.value.as_deref())?;
+        }
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_launch_config(handle: AppHandle) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
+
+    if !config.battle_net.enabled && !config.steam.enabled {
+        config.is_setup = false;
+    }
+
+    if config.is_setup {
+        update_shared_config(&mut config)?;
     }
 
     //NOTE: Temporarily advertise Steam support
@@ -71,8 +659,26 @@ fn get_launch_config(handle: AppHandle) -> Result<String, Error> {
 }
 
 #[tauri::command]
-fn setup(handle: AppHandle, platforms: Vec<&str>, is_initialized: bool) -> Result<String, Error> {
-    let mut config = config::read_config(&handle)?;
+async fn setup(
+    handle: AppHandle,
+    platforms: Vec<String>,
+    is_initialized: bool,
+) -> Result<String, Error> {
+    run_blocking(move || {
+        let _locks = lock_config_and_clients();
+        let platforms: Vec<&str> = platforms.iter().map(String::as_str).collect();
+        setup_platforms(&handle, platforms, is_initialized)
+    })
+    .await
+}
+
+/// Set up the given platforms, finding their installations and config files.
+fn setup_platforms(
+    handle: &AppHandle,
+    platforms: Vec<&str>,
+    is_initialized: bool,
+) -> Result<String, Error> {
+    let mut config = config::read_config(handle)?;
 
     if platforms.contains(&"BattleNet") {
         // Check if Battle.net is installed
@@ -87,38 +693,68 @@ fn setup(handle: AppHandle, platforms: Vec<&str>, is_initialized: bool) -> Resul
                 }
             }
         }
-        if config.battle_net.install.is_none() {
-            return Err(Error::Custom(serde_json::to_string(&SetupError {
-                error_key: ErrorKey::BattleNetInstall,
-                message: "Failed to find your Battle.net installation".to_string(),
-                platforms: Some(platforms.iter().map(|s| s.to_string()).collect()),
-            })?));
+
+        // Register the default Wine prefix if Battle.net is installed in it
+        if !cfg!(windows) && config.battle_net.prefixes.is_empty() {
+            if let Some(prefix) = battle_net::get_default_wine_prefix()
+                .and_then(|path| battle_net::find_wine_prefix(&path).ok())
+            {
+                config.battle_net.prefixes.push(prefix);
+            }
         }
 
-        // Check if Battle.net config exists
-        let battle_net_config = match &config.battle_net.config {
-            Some(battle_net_config) => battle_net_config.clone(),
-            None => {
-                let path = env::var_os("appdata").map(|path| Path::new(&path).join("Battle.net"));
-
-                if let Some(resource_path) = path {
-                    // Check if Battle.net AppData directory exists
-                    if let Ok(entries) = fs::read_dir(&resource_path) {
-                        // Check if Battle.net.config exists in the directory
-                        if let Some(target_entry) =
-                            entries.filter_map(|entry| entry.ok()).find(|entry| {
-                                entry.file_name().to_string_lossy() == battle_net::CONFIG_FILE
-                            })
-                        {
-                            let display_path = helpers::display_path_string(&target_entry.path())?;
-                            config.battle_net.config = Some(display_path.clone());
-                            display_path
+        // Battle.net may only be installed in Wine prefixes
+        let is_wine_only =
+            config.battle_net.install.is_none() && !config.battle_net.prefixes.is_empty();
+        if !is_wine_only {
+            if config.battle_net.install.is_none() {
+                return Err(Error::Custom(serde_json::to_string(&SetupError {
+                    error_key: ErrorKey::BattleNetInstall,
+                    message: "Failed to find your Battle.net installation".to_string(),
+                    platforms: Some(platforms.iter().map(|s| s.to_string()).collect()),
+                })?));
+            }
+
+            // Check if Battle.net config exists
+            let battle_net_config = match &config.battle_net.config {
+                Some(battle_net_config) => battle_net_config.clone(),
+                None => {
+                    let path =
+                        env::var_os("appdata").map(|path| Path::new(&path).join("Battle.net"));
+
+                    if let Some(resource_path) = path {
+                        // Check if Battle.net AppData directory exists
+                        if let Ok(entries) = fs::read_dir(&resource_path) {
+                            // Check if Battle.net.config exists in the directory
+                            if let Some(target_entry) =
+                                entries.filter_map(|entry| entry.ok()).find(|entry| {
+                                    entry.file_name().to_string_lossy() == battle_net::CONFIG_FILE
+                                })
+                            {
+                                let display_path =
+                                    helpers::display_path_string(&target_entry.path())?;
+                                config.battle_net.config = Some(display_path.clone());
+                                display_path
+                            } else {
+                                let display_path = helpers::display_path_string(&resource_path)?;
+                                return Err(Error::Custom(serde_json::to_string(&SetupError {
+                                    error_key: ErrorKey::BattleNetConfig,
+                                    message: format!(
+                                        "Failed to find [[{}]] file at [[{}]]",
+                                        battle_net::CONFIG_FILE,
+                                        display_path
+                                    ),
+                                    platforms: Some(
+                                        platforms.iter().map(|s| s.to_string()).collect(),
+                                    ),
+                                })?));
+                            }
                         } else {
                             let display_path = helpers::display_path_string(&resource_path)?;
                             return Err(Error::Custom(serde_json::to_string(&SetupError {
                                 error_key: ErrorKey::BattleNetConfig,
                                 message: format!(
-                                    "Failed to find [[{}]] file at [[{}]]",
+                                    "Failed to read [[{}]] file at [[{}]]",
                                     battle_net::CONFIG_FILE,
                                     display_path
                                 ),
@@ -126,120 +762,109 @@ fn setup(handle: AppHandle, platforms: Vec<&str>, is_initialized: bool) -> Resul
                             })?));
                         }
                     } else {
-                        let display_path = helpers::display_path_string(&resource_path)?;
                         return Err(Error::Custom(serde_json::to_string(&SetupError {
                             error_key: ErrorKey::BattleNetConfig,
-                            message: format!(
-                                "Failed to read [[{}]] file at [[{}]]",
-                                battle_net::CONFIG_FILE,
-                                display_path
-                            ),
+                            message: "Failed to find the Battle.net AppData directory".to_string(),
                             platforms: Some(platforms.iter().map(|s| s.to_string()).collect()),
                         })?));
                     }
-                } else {
+                }
+            };
+
+            // Read and parse Battle.net.config file
+            let file = match std::fs::File::open(&battle_net_config) {
+                Ok(file) => file,
+                Err(e) => {
                     return Err(Error::Custom(serde_json::to_string(&SetupError {
                         error_key: ErrorKey::BattleNetConfig,
-                        message: "Failed to find the Battle.net AppData directory".to_string(),
+                        message: format!(
+                            "Failed to open the [[{}]] file at [[{}]]: {}",
+                            battle_net::CONFIG_FILE,
+                            battle_net_config,
+                            e
+                        ),
                         platforms: Some(platforms.iter().map(|s| s.to_string()).collect()),
                     })?));
                 }
-            }
-        };
+            };
+            let mut json: serde_json::Value = match serde_json::from_reader(file) {
+                Ok(json) => json,
+                Err(e) => {
+                    return Err(Error::Custom(serde_json::to_string(&SetupError {
+                        error_key: ErrorKey::BattleNetConfig,
+                        message: format!(
+                            "Failed to read [[{}]] file at [[{}]]: {}",
+                            battle_net::CONFIG_FILE,
+                            battle_net_config,
+                            e
+                        ),
+                        platforms: Some(platforms.iter().map(|s| s.to_string()).collect()),
+                    })?));
+                }
+            };
 
-        // Read and parse Battle.net.config file
-        let file = match std::fs::File::open(&battle_net_config) {
-            Ok(file) => file,
-            Err(e) => {
-                return Err(Error::Custom(serde_json::to_string(&SetupError {
-                    error_key: ErrorKey::BattleNetConfig,
-                    message: format!(
-                        "Failed to open the [[{}]] file at [[{}]]: {}",
-                        battle_net::CONFIG_FILE,
-                        battle_net_config,
-                        e
-                    ),
-                    platforms: Some(platforms.iter().map(|s| s.to_string()).collect()),
-                })?));
-            }
-        };
-        let mut json: serde_json::Value = match serde_json::from_reader(file) {
-            Ok(json) => json,
-            Err(e) => {
+            // Check Overwatch installation on Battle.net
+            if let None = json
+                .get_mut("Games")
+                .and_then(|games| games.get_mut("prometheus"))
+            {
                 return Err(Error::Custom(serde_json::to_string(&SetupError {
-                    error_key: ErrorKey::BattleNetConfig,
-                    message: format!(
-                        "Failed to read [[{}]] file at [[{}]]: {}",
-                        battle_net::CONFIG_FILE,
-                        battle_net_config,
-                        e
-                    ),
-                    platforms: Some(platforms.iter().map(|s| s.to_string()).collect()),
+                    error_key: ErrorKey::NoOverwatch,
+                    message: "Unable to find an Overwatch installation on Battle.net".to_string(),
+                    platforms: None,
                 })?));
-            }
-        };
-
-        // Check Overwatch installation on Battle.net
-        if let None = json
-            .get_mut("Games")
-            .and_then(|games| games.get_mut("prometheus"))
-        {
-            return Err(Error::Custom(serde_json::to_string(&SetupError {
-                error_key: ErrorKey::NoOverwatch,
-                message: "Unable to find an Overwatch installation on Battle.net".to_string(),
-                platforms: None,
-            })?));
-        };
+            };
 
-        let mut battle_net_was_closed = false;
+            let mut closed_app: Option<process::ClosedApp> = None;
 
-        // Check and create DefaultStartupScreen if it doesn't exist
-        if let Some(client_config) = json.get_mut("Client") {
-            if client_config.get("DefaultStartupScreen").is_none() {
-                client_config
-                    .as_object_mut()
-                    .unwrap()
-                    .insert("DefaultStartupScreen".to_string(), serde_json::json!("1"));
-
-                battle_net_was_closed = battle_net::close_app();
-            } else {
-                let startup_screen = client_config["DefaultStartupScreen"]
-                    .as_str()
-                    .map(|s| s.to_string());
-
-                if startup_screen.is_none() || startup_screen.unwrap() == "0" {
+            // Check and create DefaultStartupScreen if it doesn't exist
+            if let Some(client_config) = json.get_mut("Client") {
+                if client_config.get("DefaultStartupScreen").is_none() {
                     client_config
                         .as_object_mut()
                         .unwrap()
                         .insert("DefaultStartupScreen".to_string(), serde_json::json!("1"));
 
-                    battle_net_was_closed = battle_net::close_app();
+                    check_clients_closed(&config, true, false)?;
+                    closed_app = Some(battle_net::close_app(&config));
+                } else {
+                    let startup_screen = client_config["DefaultStartupScreen"]
+                        .as_str()
+                        .map(|s| s.to_string());
+
+                    if startup_screen.is_none() || startup_screen.unwrap() == "0" {
+                        client_config
+                            .as_object_mut()
+                            .unwrap()
+                            .insert("DefaultStartupScreen".to_string(), serde_json::json!("1"));
+
+                        check_clients_closed(&config, true, false)?;
+                        closed_app = Some(battle_net::close_app(&config));
+                    }
                 }
             }
-        }
-
-        // Update config
-        let battle_net_shared = battle_net::update_config(&config)?;
-        if let Some(battle_net_shared) = battle_net_shared {
-            config.shared = battle_net_shared;
-        }
 
-        // Cleanup: Reopen Battle.net if it was closed
-        if battle_net_was_closed {
-            helpers::safe_json_write(battle_net_config, &json)?;
-            Command::new(config.battle_net.install.clone().unwrap())
-                .spawn()
-                .map_err(|_| {
-                    Error::Custom(format!(
-                        "Failed to open Battle.net at [[{}]]",
-                        config.battle_net.install.clone().unwrap()
-                    ))
-                })?;
+            // Cleanup: Reopen Battle.net if it was closed, following its restart policy
+            if let Some(closed_app) = closed_app {
+                if closed_app.result == process::CloseResult::Failed {
+                    return Err(Error::Custom(
+                        "Failed to close Battle.net. Please close it manually and try again"
+                            .to_string(),
+                    ));
+                }
+                helpers::safe_json_edit(
+                    &battle_net_config,
+                    &["Client", "DefaultStartupScreen"],
+                    "1",
+                )?;
+                battle_net::restart_app(&config, closed_app);
+            }
         }
 
         // Enable Battle.net
         config.battle_net.enabled = true;
     } else {
+        check_clients_closed(&config, config.battle_net.enabled, false)?;
         battle_net::reset_config(&mut config)?;
 
         // Disable Battle.net
@@ -249,14 +874,8 @@ fn setup(handle: AppHandle, platforms: Vec<&str>, is_initialized: bool) -> Resul
     if platforms.contains(&"Steam") {
         // Check if Steam is installed
         if config.steam.install.is_none() {
-            static LAUNCHER_PATH: &str = "Steam\\steam.exe";
-            if let Some(program_files_dir) = env::var_os("programfiles(x86)") {
-                let steam_install =
-                    Path::new(program_files_dir.to_str().unwrap()).join(LAUNCHER_PATH);
-                if steam_install.exists() {
-                    config.steam.install = Some(steam_install.to_string_lossy().to_string());
-                }
-            }
+            config.steam.install =
+                steam::find_install().map(|path| path.to_string_lossy().to_string());
         }
         if config.steam.install.is_none() {
             return Err(Error::Custom(serde_json::to_string(&SetupError {
@@ -310,11 +929,13 @@ fn setup(handle: AppHandle, platforms: Vec<&str>, is_initialized: bool) -> Resul
         }
         config.steam.enabled = true;
     } else {
+        check_clients_closed(&config, false, config.steam.enabled)?;
         steam::reset_config(&config)?;
 
         // Disable Steam
         config.steam.profiles = None;
         config.steam.configs = None;
+        config.steam.overwatch = None;
         config.steam.advertised = 0;
         config.steam.in_setup = false;
         config.steam.enabled = false;
@@ -333,45 +954,53 @@ fn setup(handle: AppHandle, platforms: Vec<&str>, is_initialized: bool) -> Resul
         config.shared.background.current = None;
         config.shared.background.is_outdated = false;
         config.shared.additional.console_enabled = false;
+        config.targets = vec![];
     } else {
         config.is_setup = true;
         config.steam.advertised = 4; // Do not advertise to new users
+        update_shared_config(&mut config)?;
     }
 
-    config::write_config(&handle, &config)?;
+    config::write_config(handle, &config)?;
+    emit_restart_offers(handle);
 
     Ok(serde_json::to_string(&config)?)
 }
 
 #[tauri::command]
-fn resolve_setup_error(
+async fn resolve_setup_error(
     handle: AppHandle,
-    key: &str,
-    path: &str,
-    platforms: Vec<&str>,
+    key: String,
+    path: String,
+    platforms: Vec<String>,
 ) -> Result<String, Error> {
-    let mut config = config::read_config(&handle)?;
+    run_blocking(move || {
+        let _locks = lock_config_and_clients();
+        let mut config = config::read_config(&handle)?;
 
-    match key {
-        "BattleNetInstall" => {
-            config.battle_net.install = Some(path.to_string());
-        }
-        "BattleNetConfig" => {
-            config.battle_net.config = Some(path.to_string());
-        }
-        "SteamInstall" | "SteamAccount" => {
-            config.steam.install = Some(path.to_string());
-        }
-        _ => {
-            return Err(Error::Custom(format!(
-                "Encountered incorrect setup resolution key [[{}]]. Please report this issue to the developer", key
-            )));
-        }
-    };
+        match key.as_str() {
+            "BattleNetInstall" => {
+                config.battle_net.install = Some(path);
+            }
+            "BattleNetConfig" => {
+                config.battle_net.config = Some(path);
+            }
+            "SteamInstall" | "SteamAccount" => {
+                config.steam.install = Some(path);
+            }
+            _ => {
+                return Err(Error::Custom(format!(
+                    "Encountered incorrect setup resolution key [[{}]]. Please report this issue to the developer", key
+                )));
+            }
+        };
 
-    config::write_config(&handle, &config)?;
+        config::write_config(&handle, &config)?;
 
-    setup(handle, platforms, false)
+        let platforms: Vec<&str> = platforms.iter().map(String::as_str).collect();
+        setup_platforms(&handle, platforms, false)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -402,27 +1031,31 @@ fn get_setup_path(key: &str) -> Result<String, Error> {
             }))?)
         }
         "SteamInstall" => {
-            let path = env::var_os("programfiles(x86)")
-                .map(|path| Path::new(&path).join("Steam"))
-                .and_then(|path| helpers::display_path_string(&path).ok());
-
-            let default_path = env::var_os("programfiles(x86)")
-                .map(|path| PathBuf::from(path))
+            let steam_path = steam::get_default_path();
+            let path = steam_path
+                .as_ref()
+                .and_then(|path| helpers::display_path_string(path).ok());
+
+            let default_path = steam_path
+                .as_ref()
+                .and_then(|path| path.parent())
+                .map(Path::to_path_buf)
                 .and_then(|path| helpers::display_path_string(&path).ok());
 
             Ok(serde_json::to_string(&serde_json::json!({
                 "path": path,
-                "defaultPath": default_path
+                "defaultPath": default_path,
+                "file": steam::LAUNCHER_FILE
             }))?)
         }
         "SteamAccount" => {
-            let path = env::var_os("programfiles(x86)")
-                .map(|path| Path::new(&path).join("Steam"))
-                .and_then(|path| helpers::display_path_string(&path).ok());
+            let path =
+                steam::get_default_path().and_then(|path| helpers::display_path_string(&path).ok());
 
             Ok(serde_json::to_string(&serde_json::json!({
                 "path": path,
-                "defaultPath": path
+                "defaultPath": path,
+                "file": steam::LAUNCHER_FILE
             }))?)
         }
         _ => Err(Error::Custom(
@@ -433,20 +1066,31 @@ fn get_setup_path(key: &str) -> Result<String, Error> {
 
 #[tauri::command]
 fn get_steam_accounts(handle: AppHandle) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
     let mut config = config::read_config(&handle)?;
 
     config.steam.configs = Some(steam::get_configs(&config)?);
+    config.steam.overwatch = steam::get_overwatch_install(&config)?;
     let profiles = steam::get_profiles(&config)?;
     config::write_config(&handle, &config)?;
 
     Ok(serde_json::to_string(&profiles)?)
 }
 
+#[tauri::command]
+fn get_steam_avatar(handle: AppHandle, steam_id: &str) -> Result<String, Error> {
+    let config = config::read_config(&handle)?;
+    let avatar = steam::get_cached_avatar(&config, steam_id)?;
+
+    Ok(serde_json::to_string(&avatar)?)
+}
+
 #[tauri::command]
 fn confirm_steam_setup(handle: AppHandle) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
     let mut config = config::read_config(&handle)?;
 
-    let steam_shared = steam::update_config(&mut config)?;
+    steam::update_config(&mut config)?;
 
     // Check that at least one account has Overwatch
     if let Some(profiles) = &config.steam.profiles {
@@ -460,29 +1104,8 @@ fn confirm_steam_setup(handle: AppHandle) -> Result<String, Error> {
         }
     }
 
-    if let Some(steam_shared) = steam_shared {
-        if config.battle_net.enabled {
-            if let (Some(battle_net_background), Some(steam_background)) = (
-                &config.shared.background.current,
-                &steam_shared.background.current,
-            ) {
-                if battle_net_background != steam_background {
-                    config.shared.background.current = None;
-                    config.shared.background.is_outdated = false;
-                }
-            }
-            config.shared.background.is_outdated =
-                config.shared.background.is_outdated || steam_shared.background.is_outdated;
-
-            config.shared.additional.console_enabled &= steam_shared.additional.console_enabled;
-        } else {
-            config.shared.background.current = steam_shared.background.current;
-            config.shared.background.is_outdated = steam_shared.background.is_outdated;
-            config.shared.additional.console_enabled = steam_shared.additional.console_enabled;
-        }
-    }
-
     config.steam.in_setup = false;
+    update_shared_config(&mut config)?;
     config::write_config(&handle, &config)?;
 
     Ok(serde_json::to_string(&config)?)
@@ -490,10 +1113,12 @@ fn confirm_steam_setup(handle: AppHandle) -> Result<String, Error> {
 
 #[tauri::command]
 fn undo_steam_setup(handle: AppHandle) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
     let mut config = config::read_config(&handle)?;
 
     config.steam.profiles = None;
     config.steam.configs = None;
+    config.steam.overwatch = None;
     config.steam.in_setup = false;
     config.steam.enabled = false;
 
@@ -507,191 +1132,1076 @@ fn undo_steam_setup(handle: AppHandle) -> Result<String, Error> {
 }
 
 #[tauri::command]
-fn get_backgrounds() -> String {
-    let backgrounds = backgrounds::get_backgrounds();
+async fn set_steam_account_enabled(
+    handle: AppHandle,
+    id: String,
+    enabled: bool,
+) -> Result<String, Error> {
+    run_blocking(move || {
+        let _locks = lock_config_and_clients();
+        let mut config = config::read_config(&handle)?;
+        update_shared_config(&mut config)?;
+
+        let profile = config
+            .steam
+            .profiles
+            .as_ref()
+            .and_then(|profiles| profiles.iter().find(|profile| profile.id == id))
+            .ok_or_else(|| {
+                Error::Custom(format!("Failed to find a Steam account with id [[{}]]", id))
+            })?;
+
+        if profile.enabled != enabled {
+            check_clients_closed(&config, false, true)?;
+
+            // Copy the state of another enabled account, or of Battle.net if there is none.
+            // The shared state is empty whenever targets disagree.
+            let source = config
+                .targets
+                .iter()
+                .find(|state| matches!(&state.target, Target::Steam(other) if *other != id))
+                .or_else(|| config.targets.first());
+            let background = source
+                .and_then(|state| state.background.current.clone())
+                .filter(|_| enabled);
+            let console_enabled =
+                enabled && source.is_some_and(|state| state.additional.console_enabled);
+
+            // Apply the state to the account, or remove OverBuddy changes from it
+            steam::set_accounts_launch_args(
+                &config,
+                &[id.as_str()],
+                (background.as_deref(), console_enabled),
+                |launch_args, (background, console_enabled)| {
+                    let launch_args =
+                        helpers::generate_background_launch_args(launch_args, background);
+                    helpers::generate_console_launch_args(Some(&launch_args), console_enabled)
+                },
+            )?;
+
+            if let Some(profile) = config
+                .steam
+                .profiles
+                .as_mut()
+                .and_then(|profiles| profiles.iter_mut().find(|profile| profile.id == id))
+            {
+                profile.enabled = enabled;
+            }
+        }
+
+        update_shared_config(&mut config)?;
+        config::write_config(&handle, &config)?;
 
-    serde_json::to_string(&backgrounds).unwrap()
+        Ok(serde_json::to_string(&config)?)
+    })
+    .await
 }
 
 #[tauri::command]
-fn set_background(handle: AppHandle, id: &str) -> Result<String, Error> {
-    let mut config = config::read_config(&handle)?;
-    let mut battle_net_error: Option<Error> = None;
-    let mut steam_error: Option<Error> = None;
+async fn add_wine_prefix(handle: AppHandle, path: String) -> Result<String, Error> {
+    run_blocking(move || {
+        let _locks = lock_config_and_clients();
+        let mut config = config::read_config(&handle)?;
+        if config.battle_net.enabled {
+            update_shared_config(&mut config)?;
+        }
 
-    if config.battle_net.enabled {
-        match battle_net::set_launch_args(
-            &config,
-            Some(id),
-            helpers::generate_background_launch_args,
-        ) {
-            Ok(_) => {}
-            Err(error) => {
-                battle_net_error = Some(error);
-            }
+        let prefix = battle_net::find_wine_prefix(Path::new(&path))?;
+        if config
+            .battle_net
+            .prefixes
+            .iter()
+            .any(|other| other.path == prefix.path)
+        {
+            return Err(Error::Custom(format!(
+                "The Wine prefix at [[{}]] has already been added",
+                prefix.path
+            )));
         }
-    }
+        let prefix_config = prefix.config.clone();
+        config.battle_net.prefixes.push(prefix);
 
-    if config.steam.enabled {
-        match steam::set_launch_args(&config, Some(id), helpers::generate_background_launch_args) {
-            Ok(_) => {}
-            Err(error) => {
-                steam_error = Some(error);
-            }
+        // Apply the Battle.net state to the new prefix. The shared state is empty whenever
+        // targets disagree.
+        if config.battle_net.enabled {
+            check_clients_closed(&config, true, false)?;
+            let source = config
+                .targets
+                .iter()
+                .find(|state| state.target == Target::BattleNet);
+            let background = source.and_then(|state| state.background.current.clone());
+            let console_enabled = source.is_some_and(|state| state.additional.console_enabled);
+            battle_net::set_files_launch_args(
+                &config,
+                &[prefix_config.as_str()],
+                (background.as_deref(), console_enabled),
+                |launch_args, (background, console_enabled)| {
+                    let launch_args =
+                        helpers::generate_background_launch_args(launch_args, background);
+                    helpers::generate_console_launch_args(Some(&launch_args), console_enabled)
+                },
+            )?;
+
+            update_shared_config(&mut config)?;
         }
-    }
 
-    if steam_error.is_some() {
-        if battle_net_error.is_some() {
-            return Err(Error::Custom(format!(
-                "Failed to apply background on Battle.net: {}\nAlso failed to apply background on Steam: {}",
-                battle_net_error.unwrap(),
-                steam_error.unwrap(),
-            )));
-        } else {
+        config::write_config(&handle, &config)?;
+
+        Ok(serde_json::to_string(&config)?)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn remove_wine_prefix(handle: AppHandle, path: String) -> Result<String, Error> {
+    run_blocking(move || {
+        let _locks = lock_config_and_clients();
+        let mut config = config::read_config(&handle)?;
+
+        let index = config
+            .battle_net
+            .prefixes
+            .iter()
+            .position(|prefix| prefix.path == path)
+            .ok_or_else(|| {
+                Error::Custom(format!("Failed to find a Wine prefix at [[{}]]", path))
+            })?;
+
+        // Remove OverBuddy changes from the prefix
+        if config.battle_net.enabled {
+            check_clients_closed(&config, true, false)?;
+            let prefix_config = config.battle_net.prefixes[index].config.clone();
+            battle_net::set_files_launch_args(
+                &config,
+                &[prefix_config.as_str()],
+                (),
+                |launch_args, _| {
+                    let launch_args = helpers::generate_background_launch_args(launch_args, None);
+                    helpers::generate_console_launch_args(Some(&launch_args), false)
+                },
+            )?;
+        }
+
+        config.battle_net.prefixes.remove(index);
+
+        // Disable Battle.net if there is no Battle.net.config left
+        if config.battle_net.config.is_none() && config.battle_net.prefixes.is_empty() {
+            config.battle_net.enabled = false;
+        }
+        if config.is_setup {
+            update_shared_config(&mut config)?;
+        }
+
+        config::write_config(&handle, &config)?;
+
+        Ok(serde_json::to_string(&config)?)
+    })
+    .await
+}
+
+#[tauri::command]
+fn get_backgrounds(handle: AppHandle) -> Result<String, Error> {
+    let config = config::read_config(&handle)?;
+    let backgrounds = backgrounds::list_backgrounds(
+        &config.custom_backgrounds,
+        config.seen_backgrounds.as_deref().unwrap_or_default(),
+    );
+
+    Ok(serde_json::to_string(&backgrounds)?)
+}
+
+#[tauri::command]
+fn get_new_backgrounds(handle: AppHandle) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
+
+    let seen = config.seen_backgrounds.as_deref().unwrap_or_default();
+    let backgrounds = backgrounds::list_new_backgrounds(seen);
+    let response = serde_json::to_string(&backgrounds)?;
+
+    // Mark the whole catalog as seen
+    config.seen_backgrounds = Some(backgrounds::get_catalog_ids());
+    config::write_config(&handle, &config)?;
+
+    Ok(response)
+}
+
+#[tauri::command]
+fn search_backgrounds(
+    handle: AppHandle,
+    query: Option<String>,
+    filter: Option<backgrounds::BackgroundFilter>,
+) -> Result<String, Error> {
+    let config = config::read_config(&handle)?;
+    let backgrounds = backgrounds::search_backgrounds(
+        query.as_deref().unwrap_or_default(),
+        &filter.unwrap_or_default(),
+        &config.custom_backgrounds,
+        config.seen_backgrounds.as_deref().unwrap_or_default(),
+    );
+
+    Ok(serde_json::to_string(&backgrounds)?)
+}
+
+/// Check the name and image of a custom background.
+fn validate_custom_background(background: &config::CustomBackground) -> Result<(), Error> {
+    if background.name.trim().is_empty() {
+        return Err(Error::Custom(
+            "Custom background name cannot be empty".to_string(),
+        ));
+    }
+    if let Some(image) = &background.image {
+        if !Path::new(image).is_file() {
             return Err(Error::Custom(format!(
-                "Failed to apply background: {}",
-                steam_error.unwrap()
+                "Failed to find an image at [[{}]]",
+                image
             )));
         }
-    } else if let Some(error) = battle_net_error {
+    }
+
+    Ok(())
+}
+
+/// Allow the webview to load the images of custom backgrounds.
+fn allow_custom_images(handle: &AppHandle, backgrounds: &[config::CustomBackground]) {
+    let scope = handle.asset_protocol_scope();
+    for image in backgrounds
+        .iter()
+        .filter_map(|background| background.image.as_ref())
+    {
+        scope.allow_file(image).ok();
+    }
+}
+
+#[tauri::command]
+fn add_custom_background(
+    handle: AppHandle,
+    id: &str,
+    name: &str,
+    image: Option<String>,
+) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
+
+    let id = &backgrounds::check_id_format(id)?;
+    if let Some(background) = backgrounds::find_background_by_id(id) {
         return Err(Error::Custom(format!(
-            "Failed to apply background: {}",
-            error
+            "Background [[{}]] is already available as [[{}]]",
+            id, background.name
         )));
     }
+    if let Some(background) = config
+        .custom_backgrounds
+        .iter()
+        .find(|background| background.id.eq_ignore_ascii_case(id))
+    {
+        return Err(Error::Custom(format!(
+            "Background [[{}]] was already added as [[{}]]",
+            id, background.name
+        )));
+    }
+
+    let background = config::CustomBackground {
+        id: id.to_string(),
+        name: name.trim().to_string(),
+        image,
+    };
+    validate_custom_background(&background)?;
+    config.custom_backgrounds.push(background);
 
-    config.shared.background.current = Some(id.to_string());
-    config.shared.background.is_outdated = false;
+    // Resolve the background if it is currently set
+    if config.is_setup {
+        update_shared_config(&mut config)?;
+    }
     config::write_config(&handle, &config)?;
+    allow_custom_images(&handle, &config.custom_backgrounds);
 
     Ok(serde_json::to_string(&config)?)
 }
 
 #[tauri::command]
-fn reset_background(handle: AppHandle) -> Result<String, Error> {
+fn edit_custom_background(
+    handle: AppHandle,
+    id: &str,
+    name: &str,
+    image: Option<String>,
+) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
     let mut config = config::read_config(&handle)?;
-    let mut battle_net_error: Option<Error> = None;
-    let mut steam_error: Option<Error> = None;
 
-    if config.battle_net.enabled {
-        match battle_net::set_launch_args(&config, None, helpers::generate_background_launch_args) {
-            Ok(_) => {}
-            Err(error) => {
-                battle_net_error = Some(error);
-            }
-        }
+    let edited = config::CustomBackground {
+        id: id.to_string(),
+        name: name.trim().to_string(),
+        image,
+    };
+    validate_custom_background(&edited)?;
+
+    let background = config
+        .custom_backgrounds
+        .iter_mut()
+        .find(|background| background.id.eq_ignore_ascii_case(id))
+        .ok_or_else(|| Error::Custom(format!("Custom background [[{}]] does not exist", id)))?;
+    *background = edited;
+    config::write_config(&handle, &config)?;
+    allow_custom_images(&handle, &config.custom_backgrounds);
+
+    Ok(serde_json::to_string(&config)?)
+}
+
+#[tauri::command]
+fn remove_custom_background(handle: AppHandle, id: &str) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
+
+    let background_count = config.custom_backgrounds.len();
+    config
+        .custom_backgrounds
+        .retain(|background| !background.id.eq_ignore_ascii_case(id));
+    if config.custom_backgrounds.len() == background_count {
+        return Err(Error::Custom(format!(
+            "Custom background [[{}]] does not exist",
+            id
+        )));
     }
 
-    if config.steam.enabled {
-        match steam::set_launch_args(&config, None, helpers::generate_background_launch_args) {
-            Ok(_) => {}
-            Err(error) => {
-                steam_error = Some(error);
-            }
-        }
+    if config.is_setup {
+        update_shared_config(&mut config)?;
     }
+    config::write_config(&handle, &config)?;
 
-    if let Some(error) = steam_error {
-        if battle_net_error.is_some() {
-            return Err(Error::Custom(format!(
-                "Failed to reset background on Battle.net: {}\nAlso failed to apply background on Steam: {}",
-                battle_net_error.unwrap(),
-                error
-            )));
-        } else {
-            return Err(Error::Custom(format!(
-                "Failed to reset background: {}",
-                error
-            )));
-        }
-    } else if let Some(error) = battle_net_error {
+    Ok(serde_json::to_string(&config)?)
+}
+
+/// Add a background to a saved list, e.g. the favourites or a collection.
+fn add_saved_background(
+    ids: &mut Vec<String>,
+    list: &str,
+    id: &str,
+    custom: &[config::CustomBackground],
+) -> Result<(), Error> {
+    let id = backgrounds::validate_background_id(id, custom)?;
+    if ids.iter().any(|saved| saved.eq_ignore_ascii_case(&id)) {
         return Err(Error::Custom(format!(
-            "Failed to reset background: {}",
-            error
+            "Background [[{}]] is already in {}",
+            id, list
         )));
     }
+    ids.push(id);
+
+    Ok(())
+}
 
-    config.shared.background.current = None;
-    config.shared.background.is_outdated = false;
+/// Remove a background from a saved list. Ids no longer in the catalog can still be removed.
+fn remove_saved_background(ids: &mut Vec<String>, list: &str, id: &str) -> Result<(), Error> {
+    let id_count = ids.len();
+    ids.retain(|saved| saved != id);
+    if ids.len() == id_count {
+        return Err(Error::Custom(format!(
+            "Background [[{}]] is not in {}",
+            id, list
+        )));
+    }
+
+    Ok(())
+}
+
+/// Move a background within a saved list, placing it last if the index is past the end.
+fn move_saved_background(
+    ids: &mut Vec<String>,
+    list: &str,
+    id: &str,
+    index: usize,
+) -> Result<(), Error> {
+    let position = ids
+        .iter()
+        .position(|saved| saved.eq_ignore_ascii_case(id))
+        .ok_or_else(|| Error::Custom(format!("Background [[{}]] is not in {}", id, list)))?;
+    let id = ids.remove(position);
+    ids.insert(index.min(ids.len()), id);
+
+    Ok(())
+}
+
+/// Check that a collection name is not empty or used by another collection.
+fn validate_collection_name(
+    config: &config::Config,
+    name: &str,
+    current: Option<&str>,
+) -> Result<(), Error> {
+    if name.is_empty() {
+        return Err(Error::Custom("Collection name cannot be empty".to_string()));
+    }
+    if config.collections.iter().any(|collection| {
+        Some(collection.name.as_str()) != current && collection.name.eq_ignore_ascii_case(name)
+    }) {
+        return Err(Error::Custom(format!(
+            "A collection named [[{}]] already exists",
+            name
+        )));
+    }
+
+    Ok(())
+}
+
+fn find_collection<'a>(
+    config: &'a mut config::Config,
+    name: &str,
+) -> Result<&'a mut config::Collection, Error> {
+    config
+        .collections
+        .iter_mut()
+        .find(|collection| collection.name == name)
+        .ok_or_else(|| Error::Custom(format!("Collection [[{}]] does not exist", name)))
+}
+
+#[tauri::command]
+fn get_favourites(handle: AppHandle) -> Result<String, Error> {
+    let config = config::read_config(&handle)?;
+    let favourites = backgrounds::list_saved_backgrounds(
+        &config.favourites,
+        &config.custom_backgrounds,
+        config.seen_backgrounds.as_deref().unwrap_or_default(),
+    );
+
+    Ok(serde_json::to_string(&favourites)?)
+}
+
+#[tauri::command]
+fn add_favourite(handle: AppHandle, id: &str) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
+
+    add_saved_background(
+        &mut config.favourites,
+        "your favourites",
+        id,
+        &config.custom_backgrounds,
+    )?;
     config::write_config(&handle, &config)?;
 
     Ok(serde_json::to_string(&config)?)
 }
 
 #[tauri::command]
-fn set_debug_console(handle: AppHandle, enable_console: bool) -> Result<String, Error> {
+fn remove_favourite(handle: AppHandle, id: &str) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
     let mut config = config::read_config(&handle)?;
-    let mut battle_net_error: Option<Error> = None;
-    let mut steam_error: Option<Error> = None;
 
-    if config.battle_net.enabled {
-        match battle_net::set_launch_args(
-            &config,
-            enable_console,
-            helpers::generate_console_launch_args,
-        ) {
-            Ok(_) => {}
-            Err(error) => {
-                battle_net_error = Some(error);
+    remove_saved_background(&mut config.favourites, "your favourites", id)?;
+    config::write_config(&handle, &config)?;
+
+    Ok(serde_json::to_string(&config)?)
+}
+
+#[tauri::command]
+fn move_favourite(handle: AppHandle, id: &str, index: usize) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
+
+    move_saved_background(&mut config.favourites, "your favourites", id, index)?;
+    config::write_config(&handle, &config)?;
+
+    Ok(serde_json::to_string(&config)?)
+}
+
+#[tauri::command]
+fn get_collections(handle: AppHandle) -> Result<String, Error> {
+    let config = config::read_config(&handle)?;
+    let collections = backgrounds::list_collections(
+        &config.collections,
+        &config.custom_backgrounds,
+        config.seen_backgrounds.as_deref().unwrap_or_default(),
+    );
+
+    Ok(serde_json::to_string(&collections)?)
+}
+
+#[tauri::command]
+fn create_collection(handle: AppHandle, name: &str) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
+
+    let name = name.trim();
+    validate_collection_name(&config, name, None)?;
+    config.collections.push(config::Collection {
+        name: name.to_string(),
+        backgrounds: vec![],
+    });
+    config::write_config(&handle, &config)?;
+
+    Ok(serde_json::to_string(&config)?)
+}
+
+#[tauri::command]
+fn rename_collection(handle: AppHandle, name: &str, new_name: &str) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
+
+    let new_name = new_name.trim();
+    validate_collection_name(&config, new_name, Some(name))?;
+    find_collection(&mut config, name)?.name = new_name.to_string();
+    config::write_config(&handle, &config)?;
+
+    Ok(serde_json::to_string(&config)?)
+}
+
+#[tauri::command]
+fn delete_collection(handle: AppHandle, name: &str) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
+
+    let collection_count = config.collections.len();
+    config
+        .collections
+        .retain(|collection| collection.name != name);
+    if config.collections.len() == collection_count {
+        return Err(Error::Custom(format!(
+            "Collection [[{}]] does not exist",
+            name
+        )));
+    }
+    config::write_config(&handle, &config)?;
+
+    Ok(serde_json::to_string(&config)?)
+}
+
+#[tauri::command]
+fn add_to_collection(handle: AppHandle, name: &str, id: &str) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
+
+    let custom = config.custom_backgrounds.clone();
+    let collection = find_collection(&mut config, name)?;
+    add_saved_background(
+        &mut collection.backgrounds,
+        &format!("[[{}]]", name),
+        id,
+        &custom,
+    )?;
+    config::write_config(&handle, &config)?;
+
+    Ok(serde_json::to_string(&config)?)
+}
+
+#[tauri::command]
+fn remove_from_collection(handle: AppHandle, name: &str, id: &str) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
+
+    let collection = find_collection(&mut config, name)?;
+    remove_saved_background(&mut collection.backgrounds, &format!("[[{}]]", name), id)?;
+    config::write_config(&handle, &config)?;
+
+    Ok(serde_json::to_string(&config)?)
+}
+
+#[tauri::command]
+fn move_in_collection(
+    handle: AppHandle,
+    name: &str,
+    id: &str,
+    index: usize,
+) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
+
+    let collection = find_collection(&mut config, name)?;
+    move_saved_background(
+        &mut collection.backgrounds,
+        &format!("[[{}]]", name),
+        id,
+        index,
+    )?;
+    config::write_config(&handle, &config)?;
+
+    Ok(serde_json::to_string(&config)?)
+}
+
+/// Turn background rotation on with the given pool, or off when there is no pool.
+#[tauri::command]
+fn set_rotation(
+    handle: AppHandle,
+    pool: Option<config::RotationPool>,
+    trigger: config::RotationTrigger,
+) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
+
+    config.rotation = match pool {
+        Some(mut pool) => {
+            if let config::RotationPool::Backgrounds(ids) = &mut pool {
+                for id in ids.iter_mut() {
+                    *id = backgrounds::validate_background_id(id, &config.custom_backgrounds)?;
+                }
             }
+            if backgrounds::get_rotation_pool(&pool, &config.custom_backgrounds).len() < 2 {
+                return Err(Error::Custom(
+                    "Rotation needs at least two backgrounds to pick from".to_string(),
+                ));
+            }
+
+            // Keep the used backgrounds unless the pool changed
+            let used = config
+                .rotation
+                .take()
+                .filter(|rotation| rotation.pool == pool)
+                .map(|rotation| rotation.used)
+                .unwrap_or_default();
+            Some(config::RotationConfig {
+                pool,
+                trigger,
+                used,
+            })
         }
-    }
+        None => None,
+    };
+    config::write_config(&handle, &config)?;
 
-    if config.steam.enabled {
-        match steam::set_launch_args(
-            &config,
-            enable_console,
-            helpers::generate_console_launch_args,
-        ) {
-            Ok(_) => {}
-            Err(error) => {
-                steam_error = Some(error);
+    Ok(serde_json::to_string(&config)?)
+}
+
+#[tauri::command]
+async fn set_background(handle: AppHandle, id: String) -> Result<String, Error> {
+    run_blocking(move || set_targets_background(&handle, Some(&id), None)).await
+}
+
+#[tauri::command]
+async fn set_target_background(
+    handle: AppHandle,
+    id: String,
+    targets: Vec<Target>,
+) -> Result<String, Error> {
+    run_blocking(move || set_targets_background(&handle, Some(&id), Some(targets))).await
+}
+
+/// Set or reset the background of the given targets, or of every target.
+fn set_targets_background(
+    handle: &AppHandle,
+    id: Option<&str>,
+    targets: Option<Vec<Target>>,
+) -> Result<String, Error> {
+    let config = change_launch_args(handle, |config| {
+        let id = id
+            .map(|id| backgrounds::validate_background_id(id, &config.custom_backgrounds))
+            .transpose()?;
+        let targets = targets.unwrap_or_else(|| get_targets(config));
+
+        Ok(vec![(targets, config::LaunchChange::Background(id))])
+    })?;
+
+    Ok(serde_json::to_string(&config)?)
+}
+
+/// Move every target with a retired background to the background replacing it.
+#[tauri::command]
+async fn migrate_retired_background(handle: AppHandle) -> Result<String, Error> {
+    run_blocking(move || {
+        let config = change_launch_args(&handle, |config| {
+            update_shared_config(config)?;
+
+            // Group targets by the background they migrate to
+            let mut migrations: Vec<(Vec<Target>, config::LaunchChange)> = vec![];
+            for state in &config.targets {
+                let Some(retired) = &state.background.retired else {
+                    continue;
+                };
+                let Some(successor) = &retired.successor else {
+                    return Err(Error::Custom(format!(
+                        "{} and there is no replacement for it",
+                        retired.reason
+                    )));
+                };
+                backgrounds::validate_background_id(successor, &config.custom_backgrounds)?;
+                let change = config::LaunchChange::Background(Some(successor.clone()));
+                match migrations.iter_mut().find(|(_, other)| *other == change) {
+                    Some((targets, _)) => targets.push(state.target.clone()),
+                    None => migrations.push((vec![state.target.clone()], change)),
+                }
             }
+            if migrations.is_empty() {
+                return Err(Error::Custom(
+                    "The current background is not retired".to_string(),
+                ));
+            }
+
+            Ok(migrations)
+        })?;
+
+        Ok(serde_json::to_string(&config)?)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn reset_background(handle: AppHandle) -> Result<String, Error> {
+    run_blocking(move || set_targets_background(&handle, None, None)).await
+}
+
+#[tauri::command]
+async fn reset_target_background(handle: AppHandle, targets: Vec<Target>) -> Result<String, Error> {
+    run_blocking(move || set_targets_background(&handle, None, Some(targets))).await
+}
+
+#[tauri::command]
+fn get_target_status(handle: AppHandle) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
+
+    update_shared_config(&mut config)?;
+    config::write_config(&handle, &config)?;
+
+    Ok(serde_json::to_string(&config.targets)?)
+}
+
+#[tauri::command]
+async fn set_debug_console(handle: AppHandle, enable_console: bool) -> Result<String, Error> {
+    run_blocking(move || {
+        let config = change_launch_args(&handle, |config| {
+            Ok(vec![(
+                get_targets(config),
+                config::LaunchChange::Console(enable_console),
+            )])
+        })?;
+
+        Ok(serde_json::to_string(&config)?)
+    })
+    .await
+}
+
+#[tauri::command]
+fn get_launch_options() -> Result<String, Error> {
+    Ok(serde_json::to_string(launch_options::get_launch_options())?)
+}
+
+#[tauri::command]
+fn get_launch_option(handle: AppHandle, name: &str) -> Result<String, Error> {
+    let option = launch_options::find_launch_option(name)
+        .ok_or_else(|| Error::Custom(format!("Unknown launch option [[{}]]", name)))?;
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
+    update_shared_config(&mut config)?;
+
+    let get_state = |target: Target, launch_args: &str| {
+        let launch_args = launch_args::LaunchArgs::parse(launch_args);
+        config::LaunchOptionState {
+            target,
+            enabled: option.is_set(&launch_args),
+            value: option.get_value(&launch_args).map(String::from),
+        }
+    };
+
+    let mut states: Vec<config::LaunchOptionState> = vec![];
+    if config.battle_net.enabled {
+        // Battle.net only reports the option as set when every config file agrees
+        let file_states: Vec<_> = battle_net::get_launch_args(&config)?
+            .iter()
+            .map(|launch_args| get_state(Target::BattleNet, launch_args))
+            .collect();
+        if let Some(first) = file_states.first() {
+            let agrees = file_states
+                .iter()
+                .all(|other| other.enabled == first.enabled && other.value == first.value);
+            states.push(if agrees {
+                first.clone()
+            } else {
+                config::LaunchOptionState {
+                    target: Target::BattleNet,
+                    enabled: false,
+                    value: None,
+                }
+            });
+        }
+    }
+    if config.steam.enabled && !config.steam.in_setup {
+        for (id, launch_args) in steam::get_accounts_launch_args(&config)? {
+            states.push(get_state(Target::Steam(id), &launch_args));
         }
     }
 
-    if steam_error.is_some() {
-        if battle_net_error.is_some() {
-            return Err(Error::Custom(format!(
-                    "Failed to apply debug console on Battle.net: {}\nAlso failed to apply debug console on Steam: {}",
-                    battle_net_error.unwrap(),
-                    steam_error.unwrap(),
-                )));
-        } else {
-            return Err(Error::Custom(format!(
-                "Failed to apply debug console: {}",
-                steam_error.unwrap()
-            )));
+    config::write_config(&handle, &config)?;
+
+    Ok(serde_json::to_string(&states)?)
+}
+
+#[tauri::command]
+async fn set_launch_option(
+    handle: AppHandle,
+    name: String,
+    enabled: bool,
+    value: Option<String>,
+) -> Result<String, Error> {
+    run_blocking(move || {
+        let option = launch_options::find_launch_option(&name)
+            .ok_or_else(|| Error::Custom(format!("Unknown launch option [[{}]]", name)))?;
+        if enabled {
+            option.validate(value.as_deref())?;
         }
-    } else if let Some(error) = battle_net_error {
+
+        let config = change_launch_args(&handle, |config| {
+            let value = match (enabled, option.name, value) {
+                (true, launch_options::LOBBY_MAP, Some(id)) => Some(
+                    backgrounds::validate_background_id(&id, &config.custom_backgrounds)?,
+                ),
+                (_, _, value) => value,
+            };
+
+            Ok(vec![(
+                get_targets(config),
+                config::LaunchChange::LaunchOption {
+                    name: option.name.to_string(),
+                    enabled,
+                    value: if enabled { value } else { None },
+                },
+            )])
+        })?;
+
+        Ok(serde_json::to_string(&config)?)
+    })
+    .await
+}
+
+#[tauri::command]
+fn create_preset(
+    handle: AppHandle,
+    name: String,
+    background: Option<String>,
+    console_enabled: bool,
+    options: Vec<config::PresetOption>,
+) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
+
+    let preset = config::Preset {
+        name: name.trim().to_string(),
+        background: background.as_deref().map(backgrounds::normalize_id),
+        console_enabled,
+        options,
+    };
+    validate_preset(&config, &preset)?;
+
+    config.presets.push(preset);
+    config::write_config(&handle, &config)?;
+
+    Ok(serde_json::to_string(&config)?)
+}
+
+#[tauri::command]
+fn rename_preset(handle: AppHandle, name: &str, new_name: &str) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
+
+    let new_name = new_name.trim();
+    if new_name.is_empty() {
+        return Err(Error::Custom("Preset name cannot be empty".to_string()));
+    }
+    if config
+        .presets
+        .iter()
+        .any(|preset| preset.name != name && preset.name.eq_ignore_ascii_case(new_name))
+    {
         return Err(Error::Custom(format!(
-            "Failed to apply debug console: {}",
-            error
+            "A preset named [[{}]] already exists",
+            new_name
         )));
     }
 
-    config.shared.additional.console_enabled = enable_console;
+    let preset = config
+        .presets
+        .iter_mut()
+        .find(|preset| preset.name == name)
+        .ok_or_else(|| Error::Custom(format!("Preset [[{}]] does not exist", name)))?;
+    preset.name = new_name.to_string();
     config::write_config(&handle, &config)?;
 
     Ok(serde_json::to_string(&config)?)
 }
 
 #[tauri::command]
-fn reset(handle: AppHandle) -> Result<String, Error> {
-    let config = config::read_config(&handle);
+fn delete_preset(handle: AppHandle, name: &str) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
 
-    if let Ok(config) = config {
-        battle_net::reset_config(&config)?;
-        steam::reset_config(&config)?;
+    let preset_count = config.presets.len();
+    config.presets.retain(|preset| preset.name != name);
+    if config.presets.len() == preset_count {
+        return Err(Error::Custom(format!("Preset [[{}]] does not exist", name)));
     }
+    config::write_config(&handle, &config)?;
 
-    let config = config::get_default_config();
+    Ok(serde_json::to_string(&config)?)
+}
+
+/// Apply every part of a preset in a single write to each config file.
+#[tauri::command]
+async fn apply_preset(handle: AppHandle, name: String) -> Result<String, Error> {
+    run_blocking(move || {
+        let config = change_launch_args(&handle, |config| {
+            let preset = config
+                .presets
+                .iter()
+                .find(|preset| preset.name == name)
+                .cloned()
+                .ok_or_else(|| Error::Custom(format!("Preset [[{}]] does not exist", name)))?;
+
+            Ok(vec![(
+                get_targets(config),
+                config::LaunchChange::Preset(preset),
+            )])
+        })?;
+
+        Ok(serde_json::to_string(&config)?)
+    })
+    .await
+}
+
+#[tauri::command]
+fn set_defer_changes(handle: AppHandle, enabled: bool) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
+
+    config.defer_changes = enabled;
     config::write_config(&handle, &config)?;
 
     Ok(serde_json::to_string(&config)?)
 }
 
+#[tauri::command]
+fn get_pending_changes(handle: AppHandle) -> Result<String, Error> {
+    let config = config::read_config(&handle)?;
+
+    Ok(serde_json::to_string(&config.pending)?)
+}
+
+#[tauri::command]
+fn cancel_pending_change(handle: AppHandle, id: u64) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
+
+    if !config.pending.iter().any(|pending| pending.id == id) {
+        return Err(Error::Custom(format!(
+            "Failed to find a pending change with id [[{}]]",
+            id
+        )));
+    }
+    config.pending.retain(|pending| pending.id != id);
+    config::write_config(&handle, &config)?;
+    handle.emit("pending_changes", &config.pending).ok();
+
+    Ok(serde_json::to_string(&config.pending)?)
+}
+
+#[tauri::command]
+fn set_restart_policy(
+    handle: AppHandle,
+    platform: &str,
+    policy: config::RestartPolicy,
+) -> Result<String, Error> {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = config::read_config(&handle)?;
+
+    match platform {
+        "BattleNet" => config.battle_net.restart = policy,
+        "Steam" => config.steam.restart = policy,
+        _ => {
+            return Err(Error::Custom(format!(
+                "Encountered an incorrect platform [[{}]]. Please report this issue to the developer",
+                platform
+            )));
+        }
+    }
+    config::write_config(&handle, &config)?;
+
+    Ok(serde_json::to_string(&config)?)
+}
+
+#[tauri::command]
+fn get_restart_offers() -> Result<String, Error> {
+    Ok(serde_json::to_string(&process::get_restart_offers())?)
+}
+
+#[tauri::command]
+async fn restart_client(
+    handle: AppHandle,
+    platform: String,
+    restart: bool,
+) -> Result<String, Error> {
+    run_blocking(move || {
+        let _lock = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let config = config::read_config(&handle)?;
+
+        let commands = process::take_restart_offer(&platform).ok_or_else(|| {
+            Error::Custom(format!(
+                "Failed to find a closed client to restart for [[{}]]",
+                platform
+            ))
+        })?;
+        if restart {
+            match platform.as_str() {
+                "BattleNet" => battle_net::relaunch(&config, &commands),
+                "Steam" => steam::relaunch(&config, &commands),
+                _ => {}
+            }
+        }
+
+        Ok(serde_json::to_string(&process::get_restart_offers())?)
+    })
+    .await
+}
+
+#[tauri::command]
+fn get_interrupted_writes(handle: AppHandle) -> Result<String, Error> {
+    let config = config::read_config(&handle)?;
+    let writes = journal::get_interrupted_writes(&get_config_files(&config));
+
+    Ok(serde_json::to_string(&writes)?)
+}
+
+#[tauri::command]
+fn recover_interrupted_write(
+    handle: AppHandle,
+    file: &str,
+    roll_forward: bool,
+) -> Result<String, Error> {
+    let _locks = lock_config_and_clients();
+    let config = config::read_config(&handle)?;
+    let files = get_config_files(&config);
+
+    let write = journal::get_interrupted_writes(&files)
+        .into_iter()
+        .find(|write| write.file == file)
+        .ok_or_else(|| {
+            Error::Custom(format!(
+                "Failed to find an interrupted write to [[{}]]",
+                file
+            ))
+        })?;
+    journal::recover(&write, roll_forward)?;
+
+    let writes = journal::get_interrupted_writes(&files);
+
+    Ok(serde_json::to_string(&writes)?)
+}
+
+#[tauri::command]
+async fn reset(handle: AppHandle) -> Result<String, Error> {
+    run_blocking(move || {
+        let _locks = lock_config_and_clients();
+        let config = config::read_config(&handle);
+
+        if let Ok(config) = config {
+            check_clients_closed(&config, config.battle_net.enabled, config.steam.enabled)?;
+            battle_net::reset_config(&config)?;
+            steam::reset_config(&config)?;
+        }
+
+        let config = config::get_default_config();
+        config::write_config(&handle, &config)?;
+
+        Ok(serde_json::to_string(&config)?)
+    })
+    .await
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -710,14 +2220,74 @@ pub fn run() {
             resolve_setup_error,
             get_setup_path,
             get_steam_accounts,
+            get_steam_avatar,
             confirm_steam_setup,
             undo_steam_setup,
+            set_steam_account_enabled,
+            add_wine_prefix,
+            remove_wine_prefix,
             get_backgrounds,
+            get_new_backgrounds,
+            search_backgrounds,
+            add_custom_background,
+            edit_custom_background,
+            remove_custom_background,
+            get_favourites,
+            add_favourite,
+            remove_favourite,
+            move_favourite,
+            get_collections,
+            create_collection,
+            rename_collection,
+            delete_collection,
+            add_to_collection,
+            remove_from_collection,
+            move_in_collection,
+            set_rotation,
             set_background,
+            set_target_background,
             reset_background,
+            reset_target_background,
+            migrate_retired_background,
+            get_target_status,
             set_debug_console,
+            get_launch_options,
+            get_launch_option,
+            set_launch_option,
+            create_preset,
+            rename_preset,
+            delete_preset,
+            apply_preset,
+            set_defer_changes,
+            get_pending_changes,
+            cancel_pending_change,
+            set_restart_policy,
+            get_restart_offers,
+            restart_client,
+            get_interrupted_writes,
+            recover_interrupted_write,
             reset
         ])
+        .setup(|app| {
+            journal::init(app.path().app_local_data_dir()?);
+
+            let progress_handle = app.handle().clone();
+            process::on_close_progress(move |progress| {
+                progress_handle.emit("close_progress", progress).ok();
+            });
+
+            migrate_seen_backgrounds(app.handle()).ok();
+            if let Ok(config) = config::read_config(app.handle()) {
+                allow_custom_images(app.handle(), &config.custom_backgrounds);
+            }
+
+            let handle = app.handle().clone();
+            thread::spawn(move || {
+                rotate_background_or_emit(&handle, config::RotationTrigger::Startup);
+                watch_pending_changes(handle)
+            });
+            Ok(())
+        })
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::ScaleFactorChanged { .. } = event {
                 window.set_size(tauri::LogicalSize::new(1024, 768)).unwrap();
@@ -1,27 +1,201 @@
 pub mod battle_net {
-    use crate::config::Config;
+    use crate::config;
+    use crate::config::{Config, RestartPolicy};
     use crate::helpers::{self, Error};
-    use crate::{backgrounds, config};
-    use serde_json::json;
+    use crate::process::{self, CloseResult, ClosedApp, ProcessCommand};
+    use std::fs;
+    #[cfg(windows)]
+    use std::os::windows::process::CommandExt;
+    use std::path::{Path, PathBuf};
     use std::process::Command;
+    use std::sync::Mutex;
+    #[cfg(not(windows))]
+    use std::thread;
+    use std::time::Duration;
     use sysinfo::System;
 
     pub static CONFIG_FILE: &str = "Battle.net.config";
 
-    /// Close all instances of Battle.net.
-    pub fn close_app() -> bool {
-        let mut flag = false;
-        let system = System::new_all();
-        for process in system.processes_by_name("Battle.net.exe".as_ref()) {
-            if process.kill() {
-                flag = true;
+    static PLATFORM: &str = "BattleNet";
+    static PROCESS_NAME: &str = "Battle.net.exe";
+    static CLOSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Wine prefixes and the runner Battle.net was closed in, until they are recorded in the
+    /// configuration.
+    static CLOSED_RUNNERS: Mutex<Vec<(String, String)>> = Mutex::new(vec![]);
+
+    /// Close all instances of Battle.net, including those running in Wine.
+    pub fn close_app(config: &Config) -> ClosedApp {
+        let closed_app = process::close(
+            PLATFORM,
+            PROCESS_NAME,
+            request_close,
+            CLOSE_TIMEOUT,
+            &get_config_files(config),
+        );
+
+        let mut runners = CLOSED_RUNNERS.lock().unwrap_or_else(|e| e.into_inner());
+        for command in &closed_app.commands {
+            if let (Some(prefix), Some(runner)) = (get_prefix(command), get_runner(command)) {
+                runners.push((display_path(&prefix), display_path(&runner)));
             }
         }
 
-        flag
+        closed_app
     }
 
-    /// Set the Battle.net launch arguments.
+    /// Remember the Wine runner of each prefix Battle.net was closed in, so it can be
+    /// opened with the same runner later.
+    pub fn record_runners(config: &mut Config) {
+        let runners =
+            std::mem::take(&mut *CLOSED_RUNNERS.lock().unwrap_or_else(|e| e.into_inner()));
+        for (path, runner) in runners {
+            if let Some(prefix) = config
+                .battle_net
+                .prefixes
+                .iter_mut()
+                .find(|prefix| Path::new(&prefix.path) == Path::new(&path))
+            {
+                prefix.runner = Some(runner);
+            }
+        }
+    }
+
+    /// Get the Wine binary a process runs with, or `None` if it does not run in Wine.
+    fn get_runner(command: &ProcessCommand) -> Option<PathBuf> {
+        let exe = command.exe.as_ref()?;
+        let name = exe.file_name()?.to_string_lossy();
+        if !name.starts_with("wine") {
+            return None;
+        }
+
+        // The preloader is started by Wine itself, so launch the binary next to it instead
+        let runner = name
+            .strip_suffix("-preloader")
+            .map(|name| exe.with_file_name(name))
+            .filter(|runner| runner.is_file());
+        Some(runner.unwrap_or_else(|| exe.clone()))
+    }
+
+    /// Get the Wine prefix a process runs in.
+    fn get_prefix(command: &ProcessCommand) -> Option<PathBuf> {
+        command
+            .env
+            .iter()
+            .find(|(key, _)| key == "WINEPREFIX")
+            .map(|(_, value)| PathBuf::from(value))
+            .or_else(get_default_wine_prefix)
+    }
+
+    /// Check whether Battle.net is running.
+    pub fn is_running() -> bool {
+        process::is_app_running(PROCESS_NAME)
+    }
+
+    /// Ask Battle.net to close its windows and exit.
+    #[cfg(windows)]
+    fn request_close(_: &System) {
+        Command::new("taskkill")
+            .args(["/IM", PROCESS_NAME])
+            .creation_flags(0x0800_0000)
+            .status()
+            .ok();
+    }
+
+    /// Ask Battle.net to close its windows and exit, from inside each Wine prefix it runs in.
+    #[cfg(not(windows))]
+    fn request_close(system: &System) {
+        for command in process::get_commands(system, PROCESS_NAME) {
+            let runner = get_runner(&command).unwrap_or_else(|| PathBuf::from("wine"));
+            let mut taskkill = Command::new(runner);
+            taskkill.args(["taskkill", "/IM", PROCESS_NAME]);
+            taskkill.envs(command.env.iter().map(|(key, value)| (key, value)));
+            if let Some(prefix) = get_prefix(&command) {
+                taskkill.env("WINEPREFIX", prefix);
+            }
+
+            // Reap taskkill in the background, Wine can take a while to start
+            if let Ok(mut child) = taskkill.spawn() {
+                thread::spawn(move || child.wait());
+            }
+        }
+    }
+
+    /// Restart Battle.net after it was closed, following its restart policy.
+    pub fn restart_app(config: &Config, closed_app: ClosedApp) {
+        if !closed_app.was_running() {
+            return;
+        }
+
+        match config.battle_net.restart {
+            RestartPolicy::Never => {}
+            RestartPolicy::Ask => process::offer_restart(PLATFORM, closed_app.commands),
+            RestartPolicy::Always => relaunch(config, &closed_app.commands),
+        }
+    }
+
+    /// Relaunch Battle.net with the command lines it was running with.
+    pub fn relaunch(config: &Config, commands: &[ProcessCommand]) {
+        if commands.is_empty() {
+            open_app(config);
+        }
+
+        for command in commands {
+            // Processes in Wine report the Windows path of the program as their first argument
+            let launch = match get_runner(command) {
+                Some(runner) => command.cmd.first().map(|program| {
+                    let mut launch = Command::new(runner);
+                    launch.arg(program).args(command.args());
+                    launch
+                }),
+                None => command.exe.as_ref().map(|exe| {
+                    let mut launch = Command::new(exe);
+                    launch.args(command.args());
+                    launch
+                }),
+            };
+
+            match launch {
+                Some(launch) => process::spawn(launch, command),
+                None => open_app(config),
+            }
+        }
+    }
+
+    /// Open every Battle.net launcher, native and in Wine prefixes.
+    fn open_app(config: &Config) {
+        if let Some(install) = &config.battle_net.install {
+            Command::new(install).spawn().ok();
+        }
+        for prefix in &config.battle_net.prefixes {
+            if let Some(install) = &prefix.install {
+                Command::new(prefix.runner.as_deref().unwrap_or("wine"))
+                    .env("WINEPREFIX", &prefix.path)
+                    .arg(install)
+                    .spawn()
+                    .ok();
+            }
+        }
+    }
+
+    /// Get every Battle.net.config file, native and in Wine prefixes.
+    pub fn get_config_files(config: &Config) -> Vec<&str> {
+        config
+            .battle_net
+            .config
+            .iter()
+            .map(String::as_str)
+            .chain(
+                config
+                    .battle_net
+                    .prefixes
+                    .iter()
+                    .map(|prefix| prefix.config.as_str()),
+            )
+            .collect()
+    }
+
+    /// Set the Battle.net launch arguments in every Battle.net.config file.
     pub fn set_launch_args<F, P>(
         config: &Config,
         params: P,
@@ -29,129 +203,273 @@ pub mod battle_net {
     ) -> Result<(), Error>
     where
         F: Fn(Option<&str>, P) -> String,
+        P: Clone,
     {
-        let battle_net_was_closed = close_app();
-        let battle_net_config = config.battle_net.config.clone().unwrap();
+        set_files_launch_args(
+            config,
+            &get_config_files(config),
+            params,
+            generate_launch_args,
+        )
+    }
+
+    /// Set the Battle.net launch arguments in the given Battle.net.config files.
+    pub fn set_files_launch_args<F, P>(
+        config: &Config,
+        files: &[&str],
+        params: P,
+        generate_launch_args: F,
+    ) -> Result<(), Error>
+    where
+        F: Fn(Option<&str>, P) -> String,
+        P: Clone,
+    {
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        let closed_app = close_app(config);
+        if closed_app.result == CloseResult::Failed {
+            return Err(Error::Custom(
+                "Failed to close Battle.net. Please close it manually and try again".to_string(),
+            ));
+        }
         let battle_net_cleanup: Box<dyn FnOnce()> = Box::new(move || {
-            if battle_net_was_closed {
-                Command::new(config.battle_net.install.clone().unwrap())
-                    .spawn()
-                    .ok();
-            }
+            restart_app(config, closed_app);
         });
 
+        for battle_net_config in files {
+            let result =
+                set_config_launch_args(battle_net_config, params.clone(), &generate_launch_args);
+
+            if result.is_err() {
+                battle_net_cleanup();
+                return Err(result.err().unwrap());
+            }
+        }
+
+        battle_net_cleanup();
+        Ok(())
+    }
+
+    /// Restore Battle.net.config files to their original contents, e.g. to undo a change that
+    /// failed on another target.
+    pub fn restore_files(config: &Config, originals: &[(String, String)]) -> Result<(), Error> {
+        if !helpers::has_changed(originals) {
+            return Ok(());
+        }
+
+        let closed_app = close_app(config);
+        if closed_app.result == CloseResult::Failed {
+            return Err(Error::Custom(
+                "Failed to close Battle.net. Please close it manually and try again".to_string(),
+            ));
+        }
+        let result = helpers::restore_originals(originals);
+        restart_app(config, closed_app);
+        result
+    }
+
+    fn set_config_launch_args<F, P>(
+        battle_net_config: &str,
+        params: P,
+        generate_launch_args: F,
+    ) -> Result<(), Error>
+    where
+        F: Fn(Option<&str>, P) -> String,
+    {
         // Read config file
-        let mut json = read_config(&config)?;
+        let json = read_config(battle_net_config)?;
 
         // Check Overwatch installation on Battle.net
-        let overwatch_config = match json
-            .get_mut("Games")
-            .and_then(|games| games.get_mut("prometheus"))
-        {
+        let overwatch_config = match json.get("Games").and_then(|games| games.get("prometheus")) {
             Some(config) => config,
             None => {
-                battle_net_cleanup();
-                return Err(Error::Custom(
-                    "Unable to find an Overwatch installation on Battle.net. If you have changed your Battle.net installation, please reset settings".to_string(),
-                ));
+                return Err(Error::Custom(format!(
+                    "Unable to find an Overwatch installation in [[{}]]. If you have changed your Battle.net installation, please reset settings",
+                    battle_net_config
+                )));
             }
         };
 
         // Get launch arguments config
-        let launch_args = match overwatch_config.get_mut("AdditionalLaunchArguments") {
-            Some(launch_args) => launch_args.as_str(),
-            None => {
-                overwatch_config
-                    .as_object_mut()
-                    .unwrap()
-                    .insert("AdditionalLaunchArguments".to_string(), json!(""));
-                overwatch_config.as_str()
-            }
-        };
+        let launch_args = overwatch_config
+            .get("AdditionalLaunchArguments")
+            .and_then(|launch_args| launch_args.as_str());
 
-        // Set launch arguments
+        // Set launch arguments, only touching their line of the file
         let new_launch_args = generate_launch_args(launch_args, params);
-        json["Games"]["prometheus"]["AdditionalLaunchArguments"] = json!(new_launch_args);
-
-        helpers::safe_json_write(battle_net_config, &json)?;
-        battle_net_cleanup();
+        if launch_args.is_none() && new_launch_args.is_empty() {
+            return Ok(());
+        }
+        helpers::safe_json_edit(
+            battle_net_config,
+            &["Games", "prometheus", "AdditionalLaunchArguments"],
+            &new_launch_args,
+        )?;
 
         Ok(())
     }
 
-    /// Update OverBuddy configuration with the current state of the Battle.net.config file.
+    /// Get the current state of the Battle.net.config files.
     ///
-    /// **Warning**: This function modifies the shared configuration fields.
-    pub fn update_config(config: &Config) -> Result<Option<config::SharedConfig>, Error> {
-        let json = read_config(config)?;
-        let mut shared_config = config::SharedConfig {
-            background: config::BackgroundConfig {
-                current: None,
-                is_outdated: false,
-            },
-            additional: config::AdditionalConfig {
-                console_enabled: false,
-            },
-        };
+    /// The background is only reported if every file agrees on it.
+    pub fn update_config(config: &Config) -> Result<config::TargetState, Error> {
+        let mut states = vec![];
+        for launch_args in get_launch_args(config)? {
+            // Get current background and debug console state from launch arguments
+            states.push(helpers::get_launch_state(
+                &launch_args,
+                &config.custom_backgrounds,
+            ));
+        }
 
-        if let Some(launch_args) = json
-            .get("Games")
-            .and_then(|games| games.get("prometheus"))
-            .and_then(|overwatch| overwatch.get("AdditionalLaunchArguments"))
-            .and_then(|launch_args| launch_args.as_str())
+        let mut state = states.first().cloned().unwrap_or_default();
+        if states
+            .iter()
+            .any(|other| other.background.current != state.background.current)
         {
-            // Get current background from launch arguments
-            let current_background = helpers::get_background(launch_args);
-
-            // TODO: Refactor adding custom
-            // if let Some(ref current_background) = current_background {
-            //     config.shared.background.current = Some(current_background.clone());
-
-            //     // NOTE: When adding custom, check if it was set by the user
-            //     if backgrounds::find_background_by_id(current_background).is_none() {
-            //         config.shared.background.is_outdated = true;
-            //     }
-            // }
-
-            // Save current background
-            shared_config.background.current =
-                current_background
-                    .as_ref()
-                    .and_then(|current_background_id| {
-                        backgrounds::find_background_by_id(current_background_id)
-                            .map(|background| background.id.to_string())
-                    });
-            shared_config.background.is_outdated =
-                shared_config.background.current.is_none() && current_background.is_some();
+            state.background.current = None;
+        }
+        state.background.is_outdated = states.iter().any(|other| other.background.is_outdated);
+        state.background.unknown = states
+            .iter()
+            .find_map(|other| other.background.unknown.clone());
+        state.background.retired = states
+            .iter()
+            .find_map(|other| other.background.retired.clone());
+        state.additional.console_enabled =
+            states.iter().all(|other| other.additional.console_enabled);
+
+        Ok(config::TargetState {
+            target: config::Target::BattleNet,
+            background: state.background,
+            additional: state.additional,
+        })
+    }
 
-            // Save debug console state
-            shared_config.additional.console_enabled = helpers::get_console_enabled(launch_args);
+    /// Get the Overwatch launch arguments from every Battle.net config file.
+    pub fn get_launch_args(config: &Config) -> Result<Vec<String>, Error> {
+        let mut all_launch_args = vec![];
+        for battle_net_config in get_config_files(config) {
+            let json = read_config(battle_net_config)?;
+            let launch_args = json
+                .get("Games")
+                .and_then(|games| games.get("prometheus"))
+                .and_then(|overwatch| overwatch.get("AdditionalLaunchArguments"))
+                .and_then(|launch_args| launch_args.as_str())
+                .unwrap_or_default();
+            all_launch_args.push(launch_args.to_string());
         }
 
-        Ok(Some(shared_config))
+        Ok(all_launch_args)
     }
 
     /// Reset all Battle.net configuration.
     pub fn reset_config(config: &Config) -> Result<(), Error> {
         if config.battle_net.enabled {
+            let (has_background, console_enabled) = config
+                .targets
+                .iter()
+                .find(|state| state.target == config::Target::BattleNet)
+                .map_or(
+                    (
+                        config.shared.background.current.is_some(),
+                        config.shared.additional.console_enabled,
+                    ),
+                    |state| {
+                        (
+                            state.background.current.is_some(),
+                            state.additional.console_enabled,
+                        )
+                    },
+                );
+
             // Reset background
-            if config.shared.background.current.is_some() {
-                set_launch_args(&config, None, helpers::generate_background_launch_args)?;
+            if has_background {
+                set_launch_args(config, None, helpers::generate_background_launch_args)?;
             }
             // Reset debug console state
-            if config.shared.additional.console_enabled {
-                set_launch_args(&config, false, helpers::generate_console_launch_args)?;
+            if console_enabled {
+                set_launch_args(config, false, helpers::generate_console_launch_args)?;
             }
         }
 
         Ok(())
     }
 
-    fn read_config(config: &Config) -> Result<serde_json::Value, Error> {
-        let battle_net_config = config.battle_net.config.clone().unwrap();
+    /// Find Battle.net.config and the Battle.net launcher in a Wine prefix.
+    pub fn find_wine_prefix(path: &Path) -> Result<config::WinePrefix, Error> {
+        let drive_c = path.join("drive_c");
+        if !drive_c.is_dir() {
+            return Err(Error::Custom(format!(
+                "Failed to find a Wine prefix at [[{}]]",
+                path.display()
+            )));
+        }
+
+        // Battle.net.config is stored in the roaming AppData of the Wine user
+        let users = fs::read_dir(drive_c.join("users")).map_err(|e| {
+            Error::Custom(format!(
+                "Failed to read the users of the Wine prefix at [[{}]]: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let battle_net_config = users
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name() != "Public")
+            .map(|entry| {
+                entry
+                    .path()
+                    .join("AppData")
+                    .join("Roaming")
+                    .join("Battle.net")
+                    .join(CONFIG_FILE)
+            })
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                Error::Custom(format!(
+                    "Failed to find [[{}]] file in the Wine prefix at [[{}]]",
+                    CONFIG_FILE,
+                    path.display()
+                ))
+            })?;
+
+        let install = ["Program Files (x86)", "Program Files"]
+            .iter()
+            .map(|dir| {
+                drive_c
+                    .join(dir)
+                    .join("Battle.net")
+                    .join("Battle.net Launcher.exe")
+            })
+            .find(|path| path.is_file());
+
+        Ok(config::WinePrefix {
+            path: display_path(path),
+            config: display_path(&battle_net_config),
+            install: install.map(|install| display_path(&install)),
+            runner: None,
+        })
+    }
+
+    /// Get the default Wine prefix, if it exists.
+    pub fn get_default_wine_prefix() -> Option<PathBuf> {
+        std::env::var_os("WINEPREFIX")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".wine")))
+            .filter(|path| path.is_dir())
+    }
+
+    fn display_path(path: &Path) -> String {
+        helpers::display_path_string(&path.to_path_buf())
+            .unwrap_or_else(|_| path.to_string_lossy().to_string())
+    }
 
+    fn read_config(battle_net_config: &str) -> Result<serde_json::Value, Error> {
         // Read and parse Battle.net.config file
-        let file = match std::fs::File::open(&battle_net_config) {
+        let file = match std::fs::File::open(battle_net_config) {
             Ok(file) => file,
             Err(e) => {
                 return Err(Error::Custom(format!(
@@ -177,36 +495,188 @@ pub mod battle_net {
 }
 
 pub mod steam {
-    use crate::backgrounds;
-    use crate::config::{self, Config, SteamProfile};
+    use crate::config::{self, Config, RestartPolicy, SteamProfile};
     use crate::helpers::{self, Error};
-    use similar::{ChangeTag, TextDiff};
-    use std::collections::VecDeque;
-    use std::fs::{self, File};
-    use std::io::{self, BufRead, BufReader, Read, Write};
-    use std::os::windows::process::CommandExt; // NOTE: Windows only
-    use std::path::Path;
+    use crate::journal;
+    use crate::launch_args::LaunchArgs;
+    use crate::process::{self, CloseResult, ClosedApp, ProcessCommand};
+    use crate::vdf;
+    use base64::prelude::{Engine, BASE64_STANDARD};
+    use std::collections::HashMap;
+    use std::env;
+    use std::fs;
+    #[cfg(windows)]
+    use std::os::windows::process::CommandExt;
+    use std::path::{Path, PathBuf};
     use std::process::Command;
-    use sysinfo::System;
+    use std::thread;
+    use std::time::Duration;
+
+    /// The file that launches Steam, located in the Steam installation directory.
+    #[cfg(windows)]
+    pub const LAUNCHER_FILE: &str = "steam.exe";
+    #[cfg(not(windows))]
+    pub const LAUNCHER_FILE: &str = "steam.sh";
+
+    #[cfg(windows)]
+    const PROCESS_NAME: &str = "steam.exe";
+    #[cfg(not(windows))]
+    const PROCESS_NAME: &str = "steam";
+
+    static PLATFORM: &str = "Steam";
+    static CLOSE_TIMEOUT: Duration = Duration::from_secs(20);
+    static FLATPAK_APP_ID: &str = "com.valvesoftware.Steam";
 
     /// Close all instances of Steam.
-    pub fn close_app() -> bool {
-        let mut flag = false;
-        let system = System::new_all();
-        for process in system.processes_by_name("steam.exe".as_ref()) {
-            if process.kill() {
-                flag = true;
+    pub fn close_app(config: &Config) -> ClosedApp {
+        let files: Vec<&str> = config
+            .steam
+            .configs
+            .iter()
+            .flatten()
+            .map(|steam_config| steam_config.file.as_str())
+            .collect();
+
+        process::close(
+            PLATFORM,
+            PROCESS_NAME,
+            |_| request_close(config),
+            CLOSE_TIMEOUT,
+            &files,
+        )
+    }
+
+    /// Check whether Steam is running.
+    pub fn is_running() -> bool {
+        process::is_app_running(PROCESS_NAME)
+    }
+
+    /// Ask Steam to exit with `steam -shutdown`, without waiting for the launcher to return.
+    fn request_close(config: &Config) {
+        let mut command = get_launcher_command(config);
+        command.arg("-shutdown");
+        #[cfg(windows)]
+        command.creation_flags(0x0800_0000);
+
+        // Reap the launcher in the background, it can take as long as Steam to exit
+        if let Ok(mut child) = command.spawn() {
+            thread::spawn(move || child.wait());
+        }
+    }
+
+    /// Get the command that runs the Steam launcher, or Steam itself for Flatpak installs.
+    fn get_launcher_command(config: &Config) -> Command {
+        match &config.steam.install {
+            Some(install) if install.contains(FLATPAK_APP_ID) => {
+                let mut command = Command::new("flatpak");
+                command.args(["run", FLATPAK_APP_ID]);
+                command
             }
+            Some(install) => Command::new(install),
+            None => Command::new(PROCESS_NAME),
         }
+    }
 
-        flag
+    /// Restart Steam after it was closed, following its restart policy.
+    fn restart_app(config: &Config, closed_app: ClosedApp) {
+        if !closed_app.was_running() {
+            return;
+        }
+
+        match config.steam.restart {
+            RestartPolicy::Never => {}
+            RestartPolicy::Ask => process::offer_restart(PLATFORM, closed_app.commands),
+            RestartPolicy::Always => relaunch(config, &closed_app.commands),
+        }
+    }
+
+    /// Relaunch Steam with the command line it was running with.
+    pub fn relaunch(config: &Config, commands: &[ProcessCommand]) {
+        // Go through the launcher, which sets up the Steam runtime on Linux
+        match commands.first() {
+            Some(command) => {
+                let mut launch = get_launcher_command(config);
+                launch.args(command.args());
+                process::spawn(launch, command);
+            }
+            None => open_app(),
+        }
+    }
+
+    /// Open Steam to the Overwatch library page.
+    fn open_app() {
+        #[cfg(windows)]
+        Command::new("cmd")
+            .args(["/C", "start", "steam://open/games/details/2357570"])
+            .creation_flags(0x0800_0000)
+            .spawn()
+            .ok();
+        #[cfg(not(windows))]
+        Command::new("xdg-open")
+            .arg("steam://open/games/details/2357570")
+            .spawn()
+            .ok();
+    }
+
+    /// Get the directories Steam is usually installed to, in order of preference.
+    #[cfg(windows)]
+    fn get_install_candidates() -> Vec<PathBuf> {
+        env::var_os("programfiles(x86)")
+            .map(|path| vec![PathBuf::from(path).join("Steam")])
+            .unwrap_or_default()
+    }
+
+    /// Get the directories Steam is usually installed to, in order of preference.
+    ///
+    /// Covers the native client (`~/.steam/steam` usually links to the real
+    /// installation) and the Flatpak client.
+    #[cfg(not(windows))]
+    fn get_install_candidates() -> Vec<PathBuf> {
+        let home = match env::var_os("HOME") {
+            Some(home) => PathBuf::from(home),
+            None => return vec![],
+        };
+        let flatpak = home.join(".var/app/com.valvesoftware.Steam");
+
+        vec![
+            home.join(".local/share/Steam"),
+            home.join(".steam/steam"),
+            flatpak.join(".local/share/Steam"),
+            flatpak.join(".steam/steam"),
+        ]
+    }
+
+    /// Find the Steam launcher in one of the usual installation directories.
+    pub fn find_install() -> Option<PathBuf> {
+        get_install_candidates()
+            .into_iter()
+            .map(|path| dunce::canonicalize(&path).unwrap_or(path))
+            .map(|path| path.join(LAUNCHER_FILE))
+            .find(|path| path.is_file())
+    }
+
+    /// Get the Steam installation directory, or the directory it is expected to be in.
+    pub fn get_default_path() -> Option<PathBuf> {
+        find_install()
+            .and_then(|path| path.parent().map(Path::to_path_buf))
+            .or_else(|| get_install_candidates().into_iter().next())
     }
 
     /// Get all Steam profiles from the Steam config files.
+    ///
+    /// **Note**: Expects `config.steam.overwatch` to be up to date.
     pub fn get_profiles(config: &Config) -> Result<Vec<SteamProfile>, Error> {
         let mut profiles: Vec<SteamProfile> = vec![];
+        let overwatch_installed = config
+            .steam
+            .overwatch
+            .as_ref()
+            .is_some_and(|install| install.state != config::SteamInstallState::Incomplete);
 
         if let Some(available_configs) = &config.steam.configs {
+            let steam_path = get_steam_path(config)?;
+            let login_users = get_login_users(&steam_path)?;
+
             for steam_config in available_configs {
                 let config_path = Path::new(&steam_config.file);
 
@@ -214,8 +684,24 @@ pub mod steam {
                     continue;
                 }
 
-                match extract_steam_user_info(steam_config, config_path) {
-                    Ok(profile) => profiles.push(profile),
+                let login_user =
+                    to_steam_id64(&steam_config.id).and_then(|steam_id| login_users.get(&steam_id));
+                match extract_steam_user_info(steam_config, config_path, login_user) {
+                    Ok(mut profile) => {
+                        // The app entry outlives uninstalls, so also require a library install
+                        profile.has_overwatch &= overwatch_installed;
+
+                        // Keep the user's choice of managed accounts
+                        if let Some(previous) =
+                            config.steam.profiles.as_ref().and_then(|previous| {
+                                previous.iter().find(|previous| previous.id == profile.id)
+                            })
+                        {
+                            profile.enabled = previous.enabled;
+                        }
+
+                        profiles.push(profile)
+                    }
                     Err(err) => {
                         return Err(Error::Custom(format!(
                             "{} while reading config file [[{}]] for Steam account [[{}]]",
@@ -236,7 +722,17 @@ pub mod steam {
         Ok(profiles)
     }
 
-    /// Set the Steam launch arguments.
+    /// Get the localconfig.vdf file of every Steam account.
+    fn get_localconfigs(config: &Config) -> Result<&[config::SteamLocalconfig], Error> {
+        match config.steam.configs.as_deref() {
+            Some(steam_configs) if !steam_configs.is_empty() => Ok(steam_configs),
+            _ => Err(Error::Custom(
+                "Failed to find any accounts in your Steam [[userdata]] folder".into(),
+            )),
+        }
+    }
+
+    /// Set the Steam launch arguments for all enabled accounts.
     pub fn set_launch_args<F, P>(
         config: &Config,
         params: P,
@@ -246,27 +742,49 @@ pub mod steam {
         F: Fn(Option<&str>, P) -> String,
         P: Clone,
     {
-        let steam_configs = config.steam.configs.as_ref().unwrap();
-        if steam_configs.is_empty() {
+        let steam_configs = get_localconfigs(config)?;
+
+        let mut accounts: Vec<&str> = vec![];
+        for steam_config in steam_configs {
+            if is_account_active(config, &steam_config.id)? {
+                accounts.push(&steam_config.id);
+            }
+        }
+
+        set_accounts_launch_args(config, &accounts, params, generate_launch_args)
+    }
+
+    /// Set the Steam launch arguments for the given accounts, regardless of whether they are enabled.
+    pub fn set_accounts_launch_args<F, P>(
+        config: &Config,
+        accounts: &[&str],
+        params: P,
+        generate_launch_args: F,
+    ) -> Result<(), Error>
+    where
+        F: Fn(Option<&str>, P) -> String,
+        P: Clone,
+    {
+        if accounts.is_empty() {
+            return Ok(());
+        }
+        let steam_configs = get_localconfigs(config)?;
+
+        let closed_app = close_app(config);
+        if closed_app.result == CloseResult::Failed {
             return Err(Error::Custom(
-                "Failed to find any accounts in your Steam [[userdata]] folder".into(),
+                "Failed to close Steam. Please close it manually and try again".to_string(),
             ));
         }
-
-        let steam_was_closed = close_app();
         let steam_cleanup: Box<dyn FnOnce()> = Box::new(move || {
-            if steam_was_closed {
-                Command::new("cmd")
-                    .args(["/C", "start", "steam://open/games/details/2357570"])
-                    .creation_flags(0x0800_0000)
-                    .spawn()
-                    .ok();
-            }
+            restart_app(config, closed_app);
         });
 
         // Modify each Steam localconfig.vdf file
         for steam_config in steam_configs {
-            if !is_overwatch_installed(config, &steam_config.id)? {
+            if !accounts.contains(&steam_config.id.as_str())
+                || !is_overwatch_installed(config, &steam_config.id)?
+            {
                 continue;
             }
 
@@ -286,10 +804,39 @@ pub mod steam {
         Ok(())
     }
 
-    /// Update OverBuddy configuration with the current state of the Battle.net.config file.
-    ///
-    /// **Warning**: This function modifies the shared configuration fields.
-    pub fn update_config(config: &mut Config) -> Result<Option<config::SharedConfig>, Error> {
+    /// Restore localconfig.vdf files to their original contents, e.g. to undo a change that
+    /// failed on another target.
+    pub fn restore_files(config: &Config, originals: &[(String, String)]) -> Result<(), Error> {
+        if !helpers::has_changed(originals) {
+            return Ok(());
+        }
+
+        let closed_app = close_app(config);
+        if closed_app.result == CloseResult::Failed {
+            return Err(Error::Custom(
+                "Failed to close Steam. Please close it manually and try again".to_string(),
+            ));
+        }
+        let result = helpers::restore_originals(originals);
+        restart_app(config, closed_app);
+        result
+    }
+
+    /// Get the localconfig.vdf files of the given accounts.
+    pub fn get_accounts_files<'a>(config: &'a Config, accounts: &[&str]) -> Vec<&'a str> {
+        config
+            .steam
+            .configs
+            .iter()
+            .flatten()
+            .filter(|steam_config| accounts.contains(&steam_config.id.as_str()))
+            .map(|steam_config| steam_config.file.as_str())
+            .collect()
+    }
+
+    /// Update Steam accounts and get the current state of each enabled account's
+    /// localconfig.vdf file.
+    pub fn update_config(config: &mut Config) -> Result<Vec<config::TargetState>, Error> {
         // Update config files
         config.steam.configs = Some(get_configs(config)?);
 
@@ -299,68 +846,47 @@ pub mod steam {
             ));
         }
 
-        // Update profiles
-        config.steam.profiles = Some(get_profiles(&config)?);
+        // Update Overwatch installation and profiles
+        config.steam.overwatch = get_overwatch_install(config)?;
+        config.steam.profiles = Some(get_profiles(config)?);
+
+        let states = get_accounts_launch_args(config)?
+            .into_iter()
+            .map(|(id, game_args)| {
+                let state = helpers::get_launch_state(&game_args, &config.custom_backgrounds);
+                config::TargetState {
+                    target: config::Target::Steam(id),
+                    background: state.background,
+                    additional: state.additional,
+                }
+            })
+            .collect();
 
-        // Update configuration state
-        let mut shared_config = config::SharedConfig {
-            background: config::BackgroundConfig {
-                current: None,
-                is_outdated: false,
-            },
-            additional: config::AdditionalConfig {
-                console_enabled: false,
-            },
-        };
-        let mut background_conflict = false;
+        Ok(states)
+    }
 
-        // Update shared state
+    /// Get the Overwatch launch arguments of every active account, without the wrappers
+    /// and variables before `%command%`.
+    pub fn get_accounts_launch_args(config: &Config) -> Result<Vec<(String, String)>, Error> {
+        // Only enabled accounts are managed by OverBuddy
+        let mut accounts = vec![];
         if let Some(available_configs) = &config.steam.configs {
             for steam_config in available_configs {
-                if !is_overwatch_installed(config, &steam_config.id)? {
+                if !is_account_active(config, &steam_config.id)? {
                     continue;
                 }
 
                 // NOTE: Currently fails when config is malformed instead of continuing.
-                let launch_args = get_config_launch_args(&steam_config.file)?.0;
+                let launch_args = get_config_launch_args(&steam_config.file)?;
 
                 if let Some(launch_args) = launch_args {
-                    // Save current background
-                    let current_background = helpers::get_background(&launch_args);
-                    let resolved_background =
-                        current_background
-                            .as_ref()
-                            .and_then(|current_background_id| {
-                                backgrounds::find_background_by_id(current_background_id)
-                                    .map(|background| background.id.to_string())
-                            });
-
-                    if !background_conflict && resolved_background.is_some() {
-                        if shared_config.background.current.is_some() {
-                            if resolved_background.unwrap()
-                                != shared_config.background.current.clone().unwrap()
-                            {
-                                shared_config.background.current = None;
-                                shared_config.background.is_outdated = false;
-
-                                background_conflict = true;
-                            }
-                        } else {
-                            shared_config.background.current = resolved_background;
-                            shared_config.background.is_outdated = false;
-                        }
-                    } else if resolved_background.is_none() && current_background.is_some() {
-                        shared_config.background.is_outdated = true;
-                    }
-
-                    // Save debug console state
-                    shared_config.additional.console_enabled =
-                        helpers::get_console_enabled(&launch_args);
+                    let (_, game_args) = split_launch_options(&launch_args);
+                    accounts.push((steam_config.id.clone(), game_args.to_string()));
                 }
             }
         }
 
-        Ok(Some(shared_config))
+        Ok(accounts)
     }
 
     /// Get all Steam configs from the Steam installation.
@@ -368,15 +894,7 @@ pub mod steam {
         let mut configs: Vec<config::SteamLocalconfig> = vec![];
 
         // Get Steam path
-        let steam_install =
-            config.steam.install.clone().ok_or_else(|| {
-                Error::Custom("Failed to find your Steam installation".to_string())
-            })?;
-        let steam_path = Path::new(&steam_install).parent().ok_or_else(|| {
-            Error::Custom(
-                "Failed to read the parent directory of your Steam installation".to_string(),
-            )
-        })?;
+        let steam_path = get_steam_path(config)?;
 
         // Fetch Steam userdata
         static CONFIG_FILE: &str = "localconfig.vdf";
@@ -399,27 +917,238 @@ pub mod steam {
         Ok(configs)
     }
 
+    /// Find the Overwatch installation across all Steam libraries.
+    pub fn get_overwatch_install(
+        config: &Config,
+    ) -> Result<Option<config::SteamOverwatchInstall>, Error> {
+        let steam_path = get_steam_path(config)?;
+
+        for library in get_library_folders(&steam_path)? {
+            let manifest_path = library.join("steamapps").join(OVERWATCH_MANIFEST);
+            if !manifest_path.is_file() {
+                continue;
+            }
+
+            let manifest = read_config(&manifest_path.to_string_lossy())?;
+            let app_state = match manifest.get_object("AppState") {
+                Some(app_state) => app_state,
+                None => continue,
+            };
+            let install_dir = match app_state.get_str("installdir") {
+                Some(install_dir) if !install_dir.is_empty() => install_dir,
+                _ => continue,
+            };
+
+            let state_flags = app_state.get_parsed::<u32>("StateFlags").unwrap_or(0);
+            let state = if state_flags & STATE_UPDATE_RUNNING != 0 {
+                config::SteamInstallState::Updating
+            } else if state_flags & STATE_FULLY_INSTALLED == 0 {
+                config::SteamInstallState::Incomplete
+            } else if state_flags & STATE_UPDATE_REQUIRED != 0 {
+                config::SteamInstallState::UpdateRequired
+            } else {
+                config::SteamInstallState::Installed
+            };
+
+            return Ok(Some(config::SteamOverwatchInstall {
+                library: library.to_string_lossy().to_string(),
+                install_dir: library
+                    .join("steamapps")
+                    .join("common")
+                    .join(install_dir)
+                    .to_string_lossy()
+                    .to_string(),
+                build_id: app_state.get_str("buildid").map(|id| id.to_string()),
+                state,
+            }));
+        }
+
+        Ok(None)
+    }
+
     /// Reset all Steam configuration.
     pub fn reset_config(config: &Config) -> Result<(), Error> {
         if config.steam.enabled {
+            let states: Vec<&config::TargetState> = config
+                .targets
+                .iter()
+                .filter(|state| matches!(state.target, config::Target::Steam(_)))
+                .collect();
+            let (has_background, console_enabled) = if states.is_empty() {
+                (
+                    config.shared.background.current.is_some(),
+                    config.shared.additional.console_enabled,
+                )
+            } else {
+                (
+                    states
+                        .iter()
+                        .any(|state| state.background.current.is_some()),
+                    states.iter().any(|state| state.additional.console_enabled),
+                )
+            };
+
             // Reset background
-            if config.shared.background.current.is_some() {
-                set_launch_args(&config, None, helpers::generate_background_launch_args)?;
+            if has_background {
+                set_launch_args(config, None, helpers::generate_background_launch_args)?;
             }
             // Reset debug console state
-            if config.shared.additional.console_enabled {
-                set_launch_args(&config, false, helpers::generate_console_launch_args)?;
+            if console_enabled {
+                set_launch_args(config, false, helpers::generate_console_launch_args)?;
             }
         }
 
         Ok(())
     }
 
+    fn get_steam_path(config: &Config) -> Result<PathBuf, Error> {
+        let steam_install =
+            config.steam.install.clone().ok_or_else(|| {
+                Error::Custom("Failed to find your Steam installation".to_string())
+            })?;
+        let steam_path = Path::new(&steam_install).parent().ok_or_else(|| {
+            Error::Custom(
+                "Failed to read the parent directory of your Steam installation".to_string(),
+            )
+        })?;
+
+        Ok(steam_path.to_path_buf())
+    }
+
+    /// Get all Steam library folders, including the Steam installation itself.
+    fn get_library_folders(steam_path: &Path) -> Result<Vec<PathBuf>, Error> {
+        let mut libraries = vec![steam_path.to_path_buf()];
+
+        let libraryfolders_path = ["steamapps", "config"]
+            .iter()
+            .map(|dir| steam_path.join(dir).join("libraryfolders.vdf"))
+            .find(|path| path.is_file());
+        let libraryfolders_path = match libraryfolders_path {
+            Some(path) => path,
+            None => return Ok(libraries),
+        };
+
+        let document = read_config(&libraryfolders_path.to_string_lossy())?;
+        let folders = match document.get_object("libraryfolders") {
+            Some(folders) => folders,
+            None => return Ok(libraries),
+        };
+
+        for (key, value) in folders.iter() {
+            // Library entries are numbered, older clients store the path directly
+            if key.parse::<u32>().is_err() {
+                continue;
+            }
+            let path = match value.as_object() {
+                Some(folder) => folder.get_str("path"),
+                None => value.as_str(),
+            };
+
+            if let Some(path) = path.filter(|path| !path.is_empty()) {
+                let path = PathBuf::from(path);
+                if !libraries.iter().any(|library| is_same_path(library, &path)) {
+                    libraries.push(path);
+                }
+            }
+        }
+
+        Ok(libraries)
+    }
+
+    fn is_same_path(a: &Path, b: &Path) -> bool {
+        match (dunce::canonicalize(a), dunce::canonicalize(b)) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => a == b,
+        }
+    }
+
     const STEAM_AVATAR_URL: &str = "https://avatars.akamai.steamstatic.com";
+    const LOCALCONFIG_ROOT: &str = "UserLocalConfigStore";
+    const OVERWATCH_APP_PATH: &str = "UserLocalConfigStore/Software/Valve/Steam/apps/2357570";
+    const OVERWATCH_MANIFEST: &str = "appmanifest_2357570.acf";
+    const COMMAND_PLACEHOLDER: &str = "%command%";
+
+    // AppState StateFlags
+    const STATE_UPDATE_REQUIRED: u32 = 2;
+    const STATE_FULLY_INSTALLED: u32 = 4;
+    const STATE_UPDATE_RUNNING: u32 = 256;
+
+    /// An account entry from `config/loginusers.vdf`.
+    struct LoginUser {
+        account_name: Option<String>,
+        persona_name: Option<String>,
+    }
+
+    /// Get all accounts that have logged in to Steam, keyed by SteamID64.
+    fn get_login_users(steam_path: &Path) -> Result<HashMap<String, LoginUser>, Error> {
+        let mut users = HashMap::new();
+
+        let loginusers_path = steam_path.join("config").join("loginusers.vdf");
+        if !loginusers_path.is_file() {
+            return Ok(users);
+        }
+
+        let document = read_config(&loginusers_path.to_string_lossy())?;
+        if let Some(entries) = document.get_object("users") {
+            for (steam_id, user) in entries.iter() {
+                let user = match user.as_object() {
+                    Some(user) => user,
+                    None => continue,
+                };
+                let non_empty = |key: &str| {
+                    user.get_str(key)
+                        .filter(|value| !value.is_empty())
+                        .map(|value| value.to_string())
+                };
+
+                users.insert(
+                    steam_id.to_string(),
+                    LoginUser {
+                        account_name: non_empty("AccountName"),
+                        persona_name: non_empty("PersonaName"),
+                    },
+                );
+            }
+        }
+
+        Ok(users)
+    }
+
+    /// Convert a `userdata` folder name (SteamID3 account id) to a SteamID64.
+    fn to_steam_id64(account_id: &str) -> Option<String> {
+        const STEAM_ID64_BASE: u64 = 76561197960265728;
+
+        account_id
+            .parse::<u64>()
+            .ok()
+            .map(|account_id| (STEAM_ID64_BASE + account_id).to_string())
+    }
+
+    /// Read an account avatar from the Steam avatar cache as a data URI, so profiles
+    /// display offline without storing images in the OverBuddy config.
+    pub fn get_cached_avatar(config: &Config, steam_id: &str) -> Result<Option<String>, Error> {
+        if steam_id.is_empty() || !steam_id.chars().all(|char| char.is_ascii_digit()) {
+            return Err(Error::Custom(format!(
+                "Encountered an invalid Steam ID [[{}]]",
+                steam_id
+            )));
+        }
+
+        let avatar_path = get_steam_path(config)?
+            .join("config")
+            .join("avatarcache")
+            .join(format!("{}.png", steam_id));
+
+        Ok(fs::read(avatar_path)
+            .ok()
+            .filter(|avatar| !avatar.is_empty())
+            .map(|avatar| format!("data:image/png;base64,{}", BASE64_STANDARD.encode(avatar))))
+    }
 
     fn extract_steam_user_info(
         steam_config: &config::SteamLocalconfig,
         config_path: &Path,
+        login_user: Option<&LoginUser>,
     ) -> Result<SteamProfile, Error> {
         let contents = match fs::read_to_string(config_path) {
             Ok(contents) => contents,
@@ -430,177 +1159,67 @@ pub mod steam {
                 )));
             }
         };
+        let document = vdf::Document::parse(&contents)
+            .map_err(|err| Error::Custom(format!("Failed to parse config file: {}", err)))?;
 
-        let outer_key = "\"UserLocalConfigStore\"";
-        let middle_key = "\n\t\"friends\"";
         let id = steam_config.id.as_str();
+        let friend = document.get_object(&format!("{}/friends/{}", LOCALCONFIG_ROOT, id));
 
-        if let Some(outer_start) = contents.to_lowercase().find(&outer_key.to_lowercase()) {
-            if let Some(middle_start) = contents[outer_start..]
-                .to_lowercase()
-                .find(&middle_key.to_lowercase())
-            {
-                if let Some(id_start) =
-                    contents[outer_start + middle_start..].find(&format!("\"{}\"", id))
-                {
-                    let object_start = outer_start + middle_start + id_start;
-                    if let Some(open_brace_index) = contents[object_start..].find('{') {
-                        let object_start = object_start + open_brace_index;
-                        let mut open_braces = 1;
-                        let mut in_quotes = false;
-
-                        for (i, c) in contents[object_start + 1..].chars().enumerate() {
-                            match c {
-                                '{' if !in_quotes => open_braces += 1,
-                                '}' if !in_quotes => {
-                                    open_braces -= 1;
-                                    if open_braces == 0 {
-                                        let end_index = object_start + i + 2;
-                                        let object_str = &contents[object_start..end_index];
-
-                                        let avatar =
-                                            extract_value(object_str, "avatar").map(|avatar| {
-                                                format!("{}/{}_full.jpg", STEAM_AVATAR_URL, avatar)
-                                            });
-
-                                        let mut name = extract_name_history(object_str);
-                                        if name.is_none() || name.as_ref().unwrap().is_empty() {
-                                            name = extract_value(object_str, "name");
-                                        }
-                                        if name.is_none() || name.as_ref().unwrap().is_empty() {
-                                            return Err(Error::Custom(
-                                                "Failed to find profile name".into(),
-                                            ));
-                                        }
-
-                                        let has_overwatch = get_overwatch_installed(&contents)?;
-
-                                        return Ok(SteamProfile {
-                                            id: id.to_string(),
-                                            name: name.unwrap(),
-                                            avatar,
-                                            has_overwatch,
-                                        });
-                                    }
-                                }
-                                '"' => in_quotes = !in_quotes,
-                                _ => {}
-                            }
-                        }
-                    }
-                }
+        let avatar = friend
+            .and_then(|friend| friend.get_str("avatar"))
+            .map(|avatar| format!("{}/{}_full.jpg", STEAM_AVATAR_URL, avatar));
+
+        // Prefer loginusers.vdf, the friends block is missing for fresh accounts
+        let name = match login_user.and_then(|user| user.persona_name.clone()) {
+            Some(name) => name,
+            None => {
+                let friend = friend.ok_or_else(|| {
+                    Error::Custom(
+                        "Reached the end of file without finding Steam user info".to_string(),
+                    )
+                })?;
+
+                friend
+                    .get_str("NameHistory/0")
+                    .filter(|name| !name.is_empty())
+                    .or_else(|| friend.get_str("name").filter(|name| !name.is_empty()))
+                    .map(|name| name.to_string())
+                    .or_else(|| login_user.and_then(|user| user.account_name.clone()))
+                    .ok_or_else(|| Error::Custom("Failed to find profile name".into()))?
             }
-        }
+        };
 
-        Err(Error::Custom(
-            "Reached the end of file without finding Steam user info".to_string(),
-        ))
-    }
+        let has_overwatch = get_overwatch_installed(&document);
 
-    fn extract_value(object_str: &str, key: &str) -> Option<String> {
-        let search_key = format!("\n\t\t\t\"{}\"", key);
-        object_str.find(&search_key).and_then(|start| {
-            let value_slice = &object_str[start + search_key.len()..];
-            let value_start = value_slice.find('"')? + 1;
-            let value_end = value_slice[value_start..].find('"')?;
-            Some(value_slice[value_start..value_start + value_end].to_string())
+        Ok(SteamProfile {
+            id: id.to_string(),
+            steam_id: to_steam_id64(id),
+            name,
+            account_name: login_user.and_then(|user| user.account_name.clone()),
+            avatar,
+            has_overwatch,
+            enabled: true,
         })
     }
 
-    fn extract_name_history(object_str: &str) -> Option<String> {
-        let search_key = "\n\t\t\t\"NameHistory\"";
-        object_str.find(search_key).and_then(|start| {
-            let after_key = &object_str[start + search_key.len()..];
-            let nested_start = after_key.find('{')? + 1;
-            let after_brace = &after_key[nested_start..];
-            let zero_key_start = after_brace.find("\"0\"")? + 3;
-            let after_zero_key = &after_brace[zero_key_start..];
-            let value_start = after_zero_key.find('"')? + 1;
-            let value_end = after_zero_key[value_start..].find('"')?;
-            Some(after_zero_key[value_start..value_start + value_end].to_string())
-        })
+    fn get_overwatch_installed(document: &vdf::Document) -> bool {
+        document.get_object(OVERWATCH_APP_PATH).is_some()
     }
 
-    fn get_overwatch_installed(contents: &str) -> Result<bool, Error> {
-        // Traverse config file to Overwatch entry
-        let mut queue: VecDeque<&str> = VecDeque::from(vec![
-            "\"UserLocalConfigStore\"",
-            "\n\t\"Software\"",
-            "\n\t\t\"Valve\"",
-            "\n\t\t\t\"Steam\"",
-            "\n\t\t\t\t\"apps\"",
-            "\n\t\t\t\t\t\"2357570\"",
-        ]);
-        let mut current_start = 0;
-        let mut current_end = contents.len();
-
-        while let Some(key) = queue.pop_front() {
-            let formatted_key = key
-                .find('\"')
-                .and_then(|start| {
-                    key[start + 1..]
-                        .find('\"')
-                        .map(|end| &key[start + 1..start + 1 + end])
-                })
-                .unwrap_or(key);
-
-            if let Some(pos) = contents[current_start..current_end]
-                .to_lowercase()
-                .find(&key.to_lowercase())
-            {
-                if formatted_key == "2357570" {
-                    break;
-                }
-
-                // Update start position
-                current_start += pos;
-
-                // Identify start of block
-                let brace_pos =
-                    contents[current_start..current_end]
-                        .find('{')
-                        .ok_or_else(|| {
-                            Error::Custom(format!(
-                                "Failed to find an opening brace for the [[{}]] key",
-                                formatted_key
-                            ))
-                        })?;
-                let block_start = current_start + brace_pos + 1;
-
-                // Identify end of block
-                let current_indent = contents[current_start..block_start]
-                    .rfind("\n")
-                    .and_then(|inner_pos| {
-                        contents[current_start + inner_pos + 1..block_start]
-                            .chars()
-                            .take_while(|&c| c == '\t')
-                            .count()
-                            .into()
-                    })
-                    .unwrap_or(0);
-
-                // Update search end position
-                let search_pattern = format!("\n{}{}", "\t".repeat(current_indent), "}");
-                current_end = contents[block_start..current_end]
-                    .find(&search_pattern)
-                    .map(|i| block_start + i + 1)
-                    .ok_or_else(|| {
-                        Error::Custom(
-                            "Failed to find the closing brace for the [[2357570]] (Overwatch) key"
-                                .to_string(),
-                        )
-                    })?;
-            } else {
-                if formatted_key == "2357570" {
-                    // Overwatch not installed on this account
-                    return Ok(false);
-                }
-
-                return Err(Error::Custom(format!("Failed to find the [[{}]] key", key)));
-            }
-        }
+    fn read_config(config_filename: &str) -> Result<vdf::Document, Error> {
+        let local_config = fs::read_to_string(config_filename).map_err(|_| {
+            Error::Custom(format!(
+                "Failed to read Steam config file at [[{}]]",
+                config_filename
+            ))
+        })?;
 
-        Ok(true)
+        vdf::Document::parse(&local_config).map_err(|err| {
+            Error::Custom(format!(
+                "Failed to parse Steam config file at [[{}]]: {}",
+                config_filename, err
+            ))
+        })
     }
 
     fn is_overwatch_installed(config: &Config, steam_id: &str) -> Result<bool, Error> {
@@ -620,195 +1239,68 @@ pub mod steam {
         ))
     }
 
-    fn verify_file_diff(file1: &str, file2: &str) -> Result<bool, String> {
-        let read_lines = |filename: &str| -> io::Result<Vec<String>> {
-            let file = File::open(filename)?;
-            let reader = BufReader::new(file);
-            reader.lines().collect()
-        };
-
-        let lines1 = read_lines(file1)
-            .map_err(|e| format!("Failed to read [[{}]]: {}", file1, e))?
-            .join("\n");
-        let lines2 = read_lines(file2)
-            .map_err(|e| format!("Failed to read [[{}]]: {}", file2, e))?
-            .join("\n");
-
-        let diff = TextDiff::from_lines(&lines1, &lines2);
-
-        let mut insert_count = 0;
-        let mut delete_count = 0;
-        for change in diff.iter_all_changes() {
-            match change.tag() {
-                ChangeTag::Insert => {
-                    if !change.value().contains("LaunchOptions") {
-                        return Err(format!(
-                            "Tried to incorrectly insert [[{}]]",
-                            change.to_string_lossy()
-                        ));
-                    }
-                    insert_count += 1;
-                }
-                ChangeTag::Delete => {
-                    if !change.value().contains("LaunchOptions") {
-                        return Err(format!(
-                            "Tried to incorrectly delete [[{}]]",
-                            change.to_string_lossy()
-                        ));
-                    }
-                    delete_count += 1;
-                }
-                _ => {}
-            }
+    /// Whether OverBuddy should manage this account, i.e. it is enabled and has Overwatch.
+    fn is_account_active(config: &Config, steam_id: &str) -> Result<bool, Error> {
+        let enabled = config
+            .steam
+            .profiles
+            .as_ref()
+            .and_then(|profiles| profiles.iter().find(|profile| profile.id == steam_id))
+            .is_none_or(|profile| profile.enabled);
 
-            if insert_count > 1 || delete_count > 1 {
-                return Err("More than one line is different".to_string());
-            }
-        }
-
-        if insert_count == 0 {
-            Ok(false)
-        } else {
-            Ok(true)
-        }
+        Ok(enabled && is_overwatch_installed(config, steam_id)?)
     }
 
-    fn get_config_launch_args(
-        config_filename: &str,
-    ) -> Result<(Option<String>, Option<usize>, Option<usize>), Error> {
-        let mut file = fs::OpenOptions::new()
-            .read(true)
-            .write(true)
-            .open(config_filename)
-            .map_err(|_| {
-                Error::Custom(format!(
-                    "Failed to open the Steam config file at [[{}]]",
-                    config_filename
-                ))
-            })?;
-        let mut local_config = String::new();
-        file.read_to_string(&mut local_config).map_err(|_| {
-            Error::Custom(format!(
-                "Failed to read Steam config file at [[{}]]",
-                config_filename
-            ))
-        })?;
-
-        // Traverse config file to Overwatch entry
-        let mut queue: VecDeque<&str> = VecDeque::from(vec![
-            "\"UserLocalConfigStore\"",
-            "\n\t\"Software\"",
-            "\n\t\t\"Valve\"",
-            "\n\t\t\t\"Steam\"",
-            "\n\t\t\t\t\"apps\"",
-            "\n\t\t\t\t\t\"2357570\"",
-        ]);
-        let mut current_start = 0;
-        let mut current_end = local_config.len();
-
-        while let Some(key) = queue.pop_front() {
-            let formatted_key = key
-                .find('\"')
-                .and_then(|start| {
-                    key[start + 1..]
-                        .find('\"')
-                        .map(|end| &key[start + 1..start + 1 + end])
-                })
-                .unwrap_or(key);
-
-            if let Some(pos) = &local_config[current_start..current_end]
-                .to_lowercase()
-                .find(&key.to_lowercase())
-            {
-                // Update start position
-                current_start += pos;
-
-                // Identify start of block
-                let brace_pos =
-                    local_config[current_start..current_end]
-                        .find('{')
-                        .ok_or_else(|| {
-                            Error::Custom(format!(
-                                "Failed to find an opening brace for the [[{}]] key in Steam config at [[{}]]",
-                                formatted_key,
-                                config_filename
-                            ))
-                        })?;
-                let block_start = current_start + brace_pos + 1;
-
-                // Identify end of block
-                let current_indent = local_config[current_start..block_start]
-                    .rfind("\n")
-                    .and_then(|inner_pos| {
-                        local_config[current_start + inner_pos + 1..block_start]
-                            .chars()
-                            .take_while(|&c| c == '\t')
-                            .count()
-                            .into()
-                    })
-                    .unwrap_or(0);
-
-                // Update search end position
-                let search_pattern = format!("\n{}{}", "\t".repeat(current_indent), "}");
-                current_end = local_config[block_start..current_end]
-                    .find(&search_pattern)
-                    .map(|i| block_start + i + 1)
-                    .ok_or_else(|| {
-                        Error::Custom(format!(
-                            "Failed to find the closing brace for the [[2357570]] (Overwatch) key in Steam config at [[{}]]", config_filename
-                        ))
-                    })?;
-            } else {
-                if formatted_key == "2357570" {
-                    // Overwatch not installed on this account
-                    return Ok((None, None, None));
-                }
-
-                return Err(Error::Custom(format!(
-                    "Failed to find the [[{}]] key in Steam config at [[{}]]",
-                    formatted_key, config_filename
-                )));
-            }
+    /// Split launch options into the command that runs the game, up to and including
+    /// `%command%`, and the game arguments after it.
+    ///
+    /// Launch options without `%command%` are all passed to the game as arguments.
+    fn split_launch_options(launch_options: &str) -> (&str, &str) {
+        match LaunchArgs::parse(launch_options).find_end(COMMAND_PLACEHOLDER) {
+            Some(end) => launch_options.split_at(end),
+            None => ("", launch_options),
         }
+    }
 
-        // Get Overwatch config block
-        let brace_pos = local_config[current_start..].find('{').ok_or_else(|| {
-                Error::Custom(format!(
-                    "Failed to find an opening brace for the [[2357570]] (Overwatch) key in Steam config at [[{}]]",
-                    config_filename
-                ))
-            })?;
-        let block_start = current_start + brace_pos + 1;
-        let block_end = current_end;
+    /// Generate launch options by changing only the game arguments, so environment
+    /// variables and wrappers before `%command%` are left untouched.
+    fn generate_launch_options<F, P>(
+        launch_options: Option<&str>,
+        params: P,
+        generate_launch_args: &F,
+    ) -> String
+    where
+        F: Fn(Option<&str>, P) -> String,
+    {
+        let (command, game_args) = split_launch_options(launch_options.unwrap_or_default());
+        let new_game_args = generate_launch_args(launch_options.map(|_| game_args), params);
 
-        // Set LaunchOptions config to background
-        if let Some(launch_options_pos) =
-            local_config[block_start..block_end].find("\"LaunchOptions\"")
+        // Without wrappers or environment variables, `%command%` is no longer needed
+        if command.is_empty()
+            || (command.trim().trim_matches('"') == COMMAND_PLACEHOLDER
+                && new_game_args.trim().is_empty())
         {
-            let value_start = block_start + launch_options_pos + "\"LaunchOptions\"".len() + 3; // Skip the key, quotes, and tab
-            let value_end = value_start
-                + local_config[value_start..block_end]
-                    .find('"')
-                    .unwrap_or(value_start);
-
-            if value_start > value_end {
-                return Err(Error::Custom(format!(
-                        "Failed to read the [[LaunchOptions]] key, inside the [[2357570]] (Overwatch) key in Steam config at [[{}]]",
-                        config_filename
-                    )));
-            }
-
-            let launch_args = &local_config[value_start..value_end];
-            return Ok((
-                Some(launch_args.to_string()),
-                Some(value_start),
-                Some(value_end),
-            ));
+            return new_game_args;
+        }
+        if new_game_args.is_empty() || new_game_args.starts_with(char::is_whitespace) {
+            format!("{}{}", command, new_game_args)
         } else {
-            return Ok((Some(String::new()), Some(block_start + 1), None));
+            format!("{} {}", command, new_game_args)
         }
     }
 
+    /// Get the Overwatch launch options, or `None` if the account has no Overwatch entry.
+    fn get_config_launch_args(config_filename: &str) -> Result<Option<String>, Error> {
+        let document = read_config(config_filename)?;
+
+        Ok(document.get_object(OVERWATCH_APP_PATH).map(|overwatch| {
+            overwatch
+                .get_str("LaunchOptions")
+                .unwrap_or_default()
+                .to_string()
+        }))
+    }
+
     fn set_config_launch_args<F, P>(
         config_filename: &str,
         params: P,
@@ -817,106 +1309,90 @@ pub mod steam {
     where
         F: Fn(Option<&str>, P) -> String,
     {
-        let backup_path = format!("{}.backup", config_filename);
+        let mut document = read_config(config_filename)?;
+        let contents = document.to_string();
+        let overwatch_config = match document.get_object_mut(OVERWATCH_APP_PATH) {
+            Some(overwatch_config) => overwatch_config,
+            None => return Ok(()),
+        };
 
-        // Create scope to release config file lock
-        {
-            let mut file = fs::OpenOptions::new()
-                .read(true)
-                .write(true)
-                .open(config_filename)
-                .map_err(|_| {
+        let launch_args = overwatch_config.get_str("LaunchOptions");
+        let new_launch_args = generate_launch_options(launch_args, params, generate_launch_args);
+        if launch_args.is_none() && new_launch_args.is_empty() {
+            return Ok(());
+        }
+        overwatch_config.set_str("LaunchOptions", &new_launch_args);
+        let new_contents = document.to_string();
+        if new_contents == contents {
+            return Ok(());
+        }
+
+        // Write and verify config file
+        journal::write(config_filename, &new_contents, |temp_path| {
+            helpers::verify_file_diff(config_filename, temp_path, "LaunchOptions")
+                .map(|_| ())
+                .map_err(|e| {
                     Error::Custom(format!(
-                        "Failed to open the Steam config file at [[{}]]",
-                        config_filename
+                        "Failed to verify the temporary file at [[{}]], {}",
+                        temp_path, e
                     ))
-                })?;
-            let mut local_config = String::new();
-            file.read_to_string(&mut local_config).map_err(|_| {
-                Error::Custom(format!(
-                    "Failed to read Steam config file at [[{}]]",
-                    config_filename
-                ))
-            })?;
+                })
+        })
+    }
 
-            let (launch_args, start_index, end_index) = get_config_launch_args(config_filename)?;
-            if let Some(launch_args) = launch_args {
-                if let (Some(start_index), Some(end_index)) = (start_index, end_index) {
-                    let new_launch_args = generate_launch_args(Some(launch_args.as_str()), params);
-                    local_config.replace_range(start_index..end_index, &new_launch_args);
-                } else if let Some(start_index) = start_index {
-                    let new_launch_args = generate_launch_args(None, params);
-                    local_config.insert_str(
-                        start_index,
-                        format!("\t\t\t\t\t\t\"LaunchOptions\"\t\t\"{}\"\n", new_launch_args)
-                            .as_str(),
-                    );
-                } else {
-                    return Err(Error::Custom(format!(
-                        "Reached unexpected state while reading launch arguments in Steam config at [[{}]]",
-                        config_filename
-                    )));
-                }
-            } else {
-                return Ok(());
-            }
+    #[cfg(test)]
+    mod tests {
+        use super::*;
 
-            // Backup config file
-            match fs::File::create(&backup_path) {
-                Ok(_) => {}
-                Err(_) => {
-                    return Err(Error::Custom(format!(
-                        "Failed to create backup of [[{}]]",
-                        config_filename
-                    )));
-                }
-            }
-            let mut file = match fs::OpenOptions::new()
-                .write(true)
-                .truncate(true)
-                .open(&backup_path)
-            {
-                Ok(file) => file,
-                Err(_) => {
-                    return Err(Error::Custom(format!(
-                        "Failed to open created backup file at [[{}]]",
-                        backup_path
-                    )));
-                }
-            };
-            match file.write_all(local_config.as_bytes()) {
-                Ok(_) => {}
-                Err(_) => {
-                    return Err(Error::Custom(format!(
-                        "Failed to write to the backup file at [[{}]]",
-                        backup_path
-                    )));
-                }
-            }
+        fn set_background(launch_options: &str, id: Option<&str>) -> String {
+            generate_launch_options(
+                Some(launch_options),
+                id,
+                &helpers::generate_background_launch_args,
+            )
+        }
 
-            // Verify backup file
-            let config_changed = verify_file_diff(&config_filename, &backup_path);
-            if config_changed.is_err() {
-                return Err(Error::Custom(format!(
-                    "Failed to verify the backup file at [[{}]], {}",
-                    backup_path,
-                    config_changed.unwrap_err()
-                )));
-            }
+        #[test]
+        fn keeps_environment_variables() {
+            assert_eq!(
+                set_background("PROTON_LOG=1 %command% -foo", Some("0x1")),
+                "PROTON_LOG=1 %command% -foo --lobbyMap=0x1"
+            );
+        }
 
-            // Apply backup file
-            if !config_changed.unwrap() {
-                return Ok(());
-            }
+        #[test]
+        fn keeps_wrapper_commands() {
+            assert_eq!(
+                set_background("gamemoderun mangohud %command% --lobbyMap=0x1", Some("0x2")),
+                "gamemoderun mangohud %command% --lobbyMap=0x2"
+            );
+            assert_eq!(
+                set_background("gamemoderun %command% --lobbyMap=0x1", None),
+                "gamemoderun %command%"
+            );
         }
 
-        // Apply backup file
-        if let Err(e) = fs::rename(backup_path, config_filename) {
-            return Err(Error::Custom(format!(
-                "Failed to replace [[{}]] with backup file. {}",
-                config_filename, e
-            )));
+        #[test]
+        fn keeps_quoted_commands() {
+            assert_eq!(
+                set_background("PROTON_LOG=1 \"%command%\" -foo", Some("0x1")),
+                "PROTON_LOG=1 \"%command%\" -foo --lobbyMap=0x1"
+            );
+            assert_eq!(
+                set_background("--lobbyMap=0x0800000000000E1F \"%command%\"", Some("0x1")),
+                "--lobbyMap=0x0800000000000E1F \"%command%\" --lobbyMap=0x1"
+            );
+            assert_eq!(set_background("\"%command%\" --lobbyMap=0x1", None), "");
+        }
+
+        #[test]
+        fn passes_everything_to_the_game_without_a_command() {
+            assert_eq!(set_background("-foo", Some("0x1")), "-foo --lobbyMap=0x1");
+            assert_eq!(set_background("", Some("0x1")), "--lobbyMap=0x1");
+            assert_eq!(
+                split_launch_options("-foo %commandX"),
+                ("", "-foo %commandX")
+            );
         }
-        return Ok(());
     }
 }
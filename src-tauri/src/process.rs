@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use sysinfo::{ProcessesToUpdate, System};
+
+// Graceful client shutdown
+//
+// Clients are first asked to exit, then given time to exit and finish writing their
+// config files, and only killed if they are still running after that.
+
+static POLL_INTERVAL: Duration = Duration::from_millis(250);
+static KILL_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long config files must stay unchanged before writes are considered finished.
+static SETTLE_TIME: Duration = Duration::from_millis(500);
+
+/// Clients that were closed and wait for the user to decide whether to restart them.
+static RESTART_OFFERS: Mutex<Option<HashMap<String, Vec<ProcessCommand>>>> = Mutex::new(None);
+/// Told about each step of closing a client, so the frontend can show what is happening.
+static CLOSE_LISTENER: OnceLock<Box<dyn Fn(CloseProgress) + Send + Sync>> = OnceLock::new();
+
+/// How a client was closed, from the gentlest step that worked.
+#[derive(serde::Serialize, Clone, Copy, PartialEq, Debug)]
+pub enum CloseResult {
+    /// The client was not running.
+    NotRunning,
+    /// The client exited after being asked to.
+    Requested,
+    /// The client did not exit in time and was killed.
+    Killed,
+    /// The client was still running after being killed.
+    Failed,
+}
+
+/// A step of closing a client.
+#[derive(serde::Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(tag = "step")]
+pub enum CloseStep {
+    /// The client was asked to exit and is given time to do so.
+    Requested,
+    /// The client exited and is given time to finish writing its config files.
+    WaitingForWrites,
+    /// The client did not exit in time and is being killed.
+    Killing,
+    /// Closing the client finished, with how it was closed.
+    Finished { result: CloseResult },
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct CloseProgress {
+    pub platform: String,
+    #[serde(flatten)]
+    pub step: CloseStep,
+}
+
+/// The command line a process was started with, captured so it can be relaunched.
+#[derive(Clone, Debug)]
+pub struct ProcessCommand {
+    pub exe: Option<PathBuf>,
+    pub cmd: Vec<String>,
+    pub cwd: Option<PathBuf>,
+    /// Wine variables, needed to relaunch processes running in a Wine prefix.
+    pub env: Vec<(String, String)>,
+}
+
+impl ProcessCommand {
+    /// Get the arguments the process was started with, without the program itself.
+    pub fn args(&self) -> &[String] {
+        self.cmd.get(1..).unwrap_or_default()
+    }
+}
+
+/// The result of closing a client, with the command lines of its main processes.
+pub struct ClosedApp {
+    pub result: CloseResult,
+    pub commands: Vec<ProcessCommand>,
+}
+
+impl ClosedApp {
+    /// Whether the client was running, and should be reopened afterwards.
+    pub fn was_running(&self) -> bool {
+        self.result != CloseResult::NotRunning
+    }
+}
+
+/// Set the listener that is told about each step of closing a client.
+pub fn on_close_progress<F>(listener: F)
+where
+    F: Fn(CloseProgress) + Send + Sync + 'static,
+{
+    let _ = CLOSE_LISTENER.set(Box::new(listener));
+}
+
+fn report(platform: &str, step: CloseStep) {
+    if let Some(listener) = CLOSE_LISTENER.get() {
+        listener(CloseProgress {
+            platform: platform.to_string(),
+            step,
+        });
+    }
+}
+
+/// Close all processes with the given name, reporting each step for `platform`.
+///
+/// `request` asks the client to exit. Once it has, waits for `files` to stop changing.
+pub fn close<F>(
+    platform: &str,
+    name: &str,
+    request: F,
+    timeout: Duration,
+    files: &[&str],
+) -> ClosedApp
+where
+    F: FnOnce(&System),
+{
+    let mut system = System::new_all();
+    if !is_running(&system, name) {
+        return ClosedApp {
+            result: CloseResult::NotRunning,
+            commands: vec![],
+        };
+    }
+    let commands = get_commands(&system, name);
+
+    // Ask the client to exit
+    report(platform, CloseStep::Requested);
+    request(&system);
+    let result = if wait_for_exit(&mut system, name, timeout) {
+        report(platform, CloseStep::WaitingForWrites);
+        wait_for_writes(files, timeout);
+        CloseResult::Requested
+    } else {
+        // Escalate to killing the client
+        report(platform, CloseStep::Killing);
+        for process in system.processes_by_exact_name(name.as_ref()) {
+            process.kill();
+        }
+        if wait_for_exit(&mut system, name, KILL_TIMEOUT) {
+            CloseResult::Killed
+        } else {
+            CloseResult::Failed
+        }
+    };
+    report(platform, CloseStep::Finished { result });
+
+    ClosedApp { result, commands }
+}
+
+/// Capture the command lines of the main processes with the given name, skipping
+/// processes started by another process with the same name.
+pub fn get_commands(system: &System, name: &str) -> Vec<ProcessCommand> {
+    let processes: Vec<_> = system.processes_by_exact_name(name.as_ref()).collect();
+    processes
+        .iter()
+        .filter(|process| {
+            !process
+                .parent()
+                .is_some_and(|parent| processes.iter().any(|other| other.pid() == parent))
+        })
+        .map(|process| ProcessCommand {
+            exe: process.exe().map(|exe| exe.to_path_buf()),
+            cmd: process
+                .cmd()
+                .iter()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect(),
+            cwd: process.cwd().map(|cwd| cwd.to_path_buf()),
+            env: process
+                .environ()
+                .iter()
+                .filter_map(|var| var.to_str()?.split_once('='))
+                .filter(|(key, _)| key.starts_with("WINE"))
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        })
+        .collect()
+}
+
+/// Spawn a command in the working directory and Wine environment of a captured process.
+pub fn spawn(mut command: Command, captured: &ProcessCommand) {
+    if let Some(cwd) = captured.cwd.as_ref().filter(|cwd| cwd.is_dir()) {
+        command.current_dir(cwd);
+    }
+    command.envs(captured.env.iter().map(|(key, value)| (key, value)));
+    command.spawn().ok();
+}
+
+/// Keep the commands of a closed client until the user decides whether to restart it.
+pub fn offer_restart(platform: &str, commands: Vec<ProcessCommand>) {
+    let mut offers = RESTART_OFFERS.lock().unwrap_or_else(|e| e.into_inner());
+    offers
+        .get_or_insert_with(HashMap::new)
+        .insert(platform.to_string(), commands);
+}
+
+/// Get the platforms with a client waiting to be restarted.
+pub fn get_restart_offers() -> Vec<String> {
+    let offers = RESTART_OFFERS.lock().unwrap_or_else(|e| e.into_inner());
+    offers
+        .as_ref()
+        .map(|offers| offers.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Take the commands of a closed client, or `None` if no restart was offered.
+pub fn take_restart_offer(platform: &str) -> Option<Vec<ProcessCommand>> {
+    let mut offers = RESTART_OFFERS.lock().unwrap_or_else(|e| e.into_inner());
+    offers.as_mut().and_then(|offers| offers.remove(platform))
+}
+
+/// Check whether Overwatch is running from any client, including under Wine or Proton.
+pub fn is_overwatch_running() -> bool {
+    static OVERWATCH_PROCESS: &str = "Overwatch.exe";
+    is_app_running(OVERWATCH_PROCESS)
+}
+
+/// Check whether any process with the given name is running.
+pub fn is_app_running(name: &str) -> bool {
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::All, true);
+    is_running(&system, name)
+}
+
+fn is_running(system: &System, name: &str) -> bool {
+    system
+        .processes_by_exact_name(name.as_ref())
+        .next()
+        .is_some()
+}
+
+fn wait_for_exit(system: &mut System, name: &str, timeout: Duration) -> bool {
+    let start = Instant::now();
+    loop {
+        system.refresh_processes(ProcessesToUpdate::All, true);
+        if !is_running(system, name) {
+            return true;
+        }
+        if start.elapsed() >= timeout {
+            return false;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn wait_for_writes(files: &[&str], timeout: Duration) {
+    let get_modified = || -> Vec<Option<SystemTime>> {
+        files
+            .iter()
+            .map(|file| fs::metadata(file).and_then(|meta| meta.modified()).ok())
+            .collect()
+    };
+
+    let start = Instant::now();
+    let mut modified = get_modified();
+    let mut unchanged_since = Instant::now();
+    while unchanged_since.elapsed() < SETTLE_TIME && start.elapsed() < timeout {
+        thread::sleep(POLL_INTERVAL);
+        let current = get_modified();
+        if current != modified {
+            modified = current;
+            unchanged_since = Instant::now();
+        }
+    }
+}
@@ -0,0 +1,695 @@
+use std::fmt;
+use std::str::FromStr;
+
+// Text KeyValues (VDF) documents
+//
+// Every token keeps its original text and every run of whitespace or comments is stored
+// alongside the entry it precedes, so an unmodified document serializes back byte for byte.
+
+#[derive(Debug, thiserror::Error)]
+#[error("{message} at line {line}, column {column}")]
+pub struct ParseError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A parsed VDF file.
+#[derive(Debug, Clone)]
+pub struct Document {
+    root: Object,
+}
+
+/// A block of key-value entries, either the document root or the contents of `{ ... }`.
+#[derive(Debug, Clone, Default)]
+pub struct Object {
+    entries: Vec<Entry>,
+    trailing: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    leading: String,
+    key: Token,
+    value: Value,
+    condition: Option<Condition>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    String { separator: String, token: Token },
+    Object { separator: String, object: Object },
+}
+
+#[derive(Debug, Clone)]
+pub struct Token {
+    raw: String,
+    value: String,
+}
+
+/// A platform conditional such as `[$WIN32]` or `[!$OSX]`.
+#[derive(Debug, Clone)]
+pub struct Condition {
+    leading: String,
+    raw: String,
+}
+
+impl Document {
+    pub fn parse(input: &str) -> Result<Document, ParseError> {
+        let mut parser = Parser::new(input);
+        let root = parser.parse_object(true)?;
+
+        Ok(Document { root })
+    }
+
+    pub fn get_object(&self, path: &str) -> Option<&Object> {
+        self.root.get_object(path)
+    }
+
+    pub fn get_object_mut(&mut self, path: &str) -> Option<&mut Object> {
+        self.root.get_object_mut(path)
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.root.write(f)
+    }
+}
+
+impl Object {
+    /// Iterate over the entries that apply to the current platform.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.is_active())
+            .map(|entry| (entry.key.value.as_str(), &entry.value))
+    }
+
+    /// Get a value by its slash-separated path, e.g. `Software/Valve/Steam/apps/2357570`.
+    pub fn get(&self, path: &str) -> Option<&Value> {
+        let (parent, key) = match path.rsplit_once('/') {
+            Some((parent, key)) => (self.get_object(parent)?, key),
+            None => (self, path),
+        };
+
+        parent.find(key).map(|index| &parent.entries[index].value)
+    }
+
+    pub fn get_str(&self, path: &str) -> Option<&str> {
+        self.get(path).and_then(Value::as_str)
+    }
+
+    pub fn get_parsed<T: FromStr>(&self, path: &str) -> Option<T> {
+        self.get_str(path)
+            .and_then(|value| value.trim().parse().ok())
+    }
+
+    pub fn get_object(&self, path: &str) -> Option<&Object> {
+        path.split('/')
+            .filter(|key| !key.is_empty())
+            .try_fold(self, |object, key| {
+                let index = object.find(key)?;
+                object.entries[index].value.as_object()
+            })
+    }
+
+    pub fn get_object_mut(&mut self, path: &str) -> Option<&mut Object> {
+        path.split('/')
+            .filter(|key| !key.is_empty())
+            .try_fold(self, |object, key| {
+                let index = object.find(key)?;
+                object.entries[index].value.as_object_mut()
+            })
+    }
+
+    /// Set a string value directly inside this object, keeping the existing formatting when the
+    /// key already exists and matching the indentation of its siblings when it does not.
+    pub fn set_str(&mut self, key: &str, value: &str) {
+        let separator = self.string_separator();
+        if let Some(index) = self.find(key) {
+            let entry = &mut self.entries[index];
+            match &mut entry.value {
+                Value::String { token, .. } => {
+                    if token.value != value {
+                        *token = Token::new(value, token.is_quoted());
+                    }
+                }
+                Value::Object { .. } => {
+                    entry.value = Value::String {
+                        separator,
+                        token: Token::new(value, true),
+                    };
+                }
+            }
+            return;
+        }
+
+        let leading = format!("\n{}", self.child_indent());
+        self.entries.push(Entry {
+            leading,
+            key: Token::new(key, true),
+            value: Value::String {
+                separator,
+                token: Token::new(value, true),
+            },
+            condition: None,
+        });
+    }
+
+    fn find(&self, key: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.key.value.eq_ignore_ascii_case(key) && entry.is_active())
+    }
+
+    /// Get the whitespace between keys and string values used by the other entries, or the
+    /// two tabs Steam writes when there are none.
+    fn string_separator(&self) -> String {
+        self.entries
+            .iter()
+            .find_map(|entry| match &entry.value {
+                Value::String { separator, .. }
+                    if !separator.is_empty()
+                        && separator.chars().all(|c| c == ' ' || c == '\t') =>
+                {
+                    Some(separator.clone())
+                }
+                _ => None,
+            })
+            .unwrap_or_else(|| "\t\t".to_string())
+    }
+
+    fn child_indent(&self) -> String {
+        if let Some(entry) = self.entries.last() {
+            if let Some(indent) = last_line(&entry.leading) {
+                return indent.to_string();
+            }
+        }
+
+        // Empty object, indent one level deeper than the closing brace
+        format!("{}\t", last_line(&self.trailing).unwrap_or(""))
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            f.write_str(&entry.leading)?;
+            f.write_str(&entry.key.raw)?;
+            match &entry.value {
+                Value::String { separator, token } => {
+                    f.write_str(separator)?;
+                    f.write_str(&token.raw)?;
+                    if let Some(condition) = &entry.condition {
+                        f.write_str(&condition.leading)?;
+                        f.write_str(&condition.raw)?;
+                    }
+                }
+                Value::Object { separator, object } => {
+                    if let Some(condition) = &entry.condition {
+                        f.write_str(&condition.leading)?;
+                        f.write_str(&condition.raw)?;
+                    }
+                    f.write_str(separator)?;
+                    f.write_str("{")?;
+                    object.write(f)?;
+                    f.write_str("}")?;
+                }
+            }
+        }
+        f.write_str(&self.trailing)
+    }
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String { token, .. } => Some(&token.value),
+            Value::Object { .. } => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&Object> {
+        match self {
+            Value::Object { object, .. } => Some(object),
+            Value::String { .. } => None,
+        }
+    }
+
+    pub fn as_object_mut(&mut self) -> Option<&mut Object> {
+        match self {
+            Value::Object { object, .. } => Some(object),
+            Value::String { .. } => None,
+        }
+    }
+}
+
+impl Token {
+    fn new(value: &str, quoted: bool) -> Token {
+        let needs_quotes = value.is_empty()
+            || value
+                .chars()
+                .any(|c| c.is_whitespace() || matches!(c, '"' | '{' | '}' | '[' | '\\'));
+        let raw = if quoted || needs_quotes {
+            format!("\"{}\"", escape(value))
+        } else {
+            value.to_string()
+        };
+
+        Token {
+            raw,
+            value: value.to_string(),
+        }
+    }
+
+    fn is_quoted(&self) -> bool {
+        self.raw.starts_with('"')
+    }
+}
+
+impl Entry {
+    fn is_active(&self) -> bool {
+        self.condition.as_ref().is_none_or(Condition::evaluate)
+    }
+}
+
+impl Condition {
+    /// Evaluate the conditional against the platform OverBuddy is running on.
+    fn evaluate(&self) -> bool {
+        let expression = self.raw.trim_start_matches('[').trim_end_matches(']');
+        expression.split("||").any(|any| {
+            any.split("&&").all(|term| {
+                let term = term.trim();
+                match term.strip_prefix('!') {
+                    Some(symbol) => !is_platform_defined(symbol.trim()),
+                    None => is_platform_defined(term),
+                }
+            })
+        })
+    }
+}
+
+fn is_platform_defined(symbol: &str) -> bool {
+    match symbol.trim_start_matches('$').to_ascii_uppercase().as_str() {
+        "WIN32" | "WINDOWS" => cfg!(windows),
+        "WIN64" => cfg!(all(windows, target_pointer_width = "64")),
+        "LINUX" => cfg!(target_os = "linux"),
+        "OSX" => cfg!(target_os = "macos"),
+        "POSIX" => cfg!(unix),
+        _ => false,
+    }
+}
+
+fn last_line(trivia: &str) -> Option<&str> {
+    trivia
+        .rfind('\n')
+        .map(|index| &trivia[index + 1..])
+        .filter(|line| line.chars().all(|c| c == ' ' || c == '\t'))
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Parser<'a> {
+        Parser { input, position: 0 }
+    }
+
+    fn parse_object(&mut self, is_root: bool) -> Result<Object, ParseError> {
+        let mut object = Object::default();
+
+        loop {
+            let leading = self.parse_trivia();
+            match self.peek() {
+                None if is_root => {
+                    object.trailing = leading;
+                    return Ok(object);
+                }
+                None => return Err(self.error("Unexpected end of file, expected [[}]]")),
+                Some('}') if !is_root => {
+                    object.trailing = leading;
+                    self.position += 1;
+                    return Ok(object);
+                }
+                Some('}') => return Err(self.error("Unexpected [[}]]")),
+                Some('{') => return Err(self.error("Expected a key but found [[{]]")),
+                Some(_) => {
+                    let key = self.parse_token()?;
+                    let entry = self.parse_entry(leading, key)?;
+                    object.entries.push(entry);
+                }
+            }
+        }
+    }
+
+    fn parse_entry(&mut self, leading: String, key: Token) -> Result<Entry, ParseError> {
+        let mut separator = self.parse_trivia();
+
+        // Conditionals may sit between a key and its block
+        let mut condition = None;
+        if self.peek() == Some('[') {
+            condition = Some(Condition {
+                leading: separator,
+                raw: self.parse_condition()?,
+            });
+            separator = self.parse_trivia();
+        }
+
+        match self.peek() {
+            None => Err(self.error(&format!(
+                "Unexpected end of file, expected a value for [[{}]]",
+                key.value
+            ))),
+            Some('}') => Err(self.error(&format!(
+                "Expected a value for [[{}]] but found [[}}]]",
+                key.value
+            ))),
+            Some('{') => {
+                self.position += 1;
+                let object = self.parse_object(false)?;
+                Ok(Entry {
+                    leading,
+                    key,
+                    value: Value::Object { separator, object },
+                    condition,
+                })
+            }
+            Some(_) if condition.is_some() => Err(self.error("Expected [[{]] after a conditional")),
+            Some(_) => {
+                let token = self.parse_token()?;
+
+                // Conditionals may follow a string value on the same line
+                let checkpoint = self.position;
+                let condition_leading = self.parse_inline_whitespace();
+                if self.peek() == Some('[') {
+                    condition = Some(Condition {
+                        leading: condition_leading,
+                        raw: self.parse_condition()?,
+                    });
+                } else {
+                    self.position = checkpoint;
+                }
+
+                Ok(Entry {
+                    leading,
+                    key,
+                    value: Value::String { separator, token },
+                    condition,
+                })
+            }
+        }
+    }
+
+    fn parse_token(&mut self) -> Result<Token, ParseError> {
+        let start = self.position;
+
+        if self.peek() == Some('"') {
+            self.position += 1;
+            let mut value = String::new();
+            loop {
+                match self.next() {
+                    None => return Err(self.error("Unterminated quoted string")),
+                    Some('"') => break,
+                    Some('\\') => match self.next() {
+                        Some('n') => value.push('\n'),
+                        Some('t') => value.push('\t'),
+                        Some('\\') => value.push('\\'),
+                        Some('"') => value.push('"'),
+                        Some(c) => {
+                            value.push('\\');
+                            value.push(c);
+                        }
+                        None => return Err(self.error("Unterminated quoted string")),
+                    },
+                    Some(c) => value.push(c),
+                }
+            }
+
+            return Ok(Token {
+                raw: self.input[start..self.position].to_string(),
+                value,
+            });
+        }
+
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || matches!(c, '"' | '{' | '}') {
+                break;
+            }
+            self.position += c.len_utf8();
+        }
+
+        let raw = &self.input[start..self.position];
+        Ok(Token {
+            raw: raw.to_string(),
+            value: raw.to_string(),
+        })
+    }
+
+    fn parse_condition(&mut self) -> Result<String, ParseError> {
+        let start = self.position;
+        match self.input[start..].find(']') {
+            Some(end) if !self.input[start..start + end].contains('\n') => {
+                self.position = start + end + 1;
+                Ok(self.input[start..self.position].to_string())
+            }
+            _ => Err(self.error("Unterminated conditional")),
+        }
+    }
+
+    /// Consume whitespace and `//` comments.
+    fn parse_trivia(&mut self) -> String {
+        let start = self.position;
+        loop {
+            match self.peek() {
+                Some(c) if c.is_whitespace() || c == '\u{feff}' => {
+                    self.position += c.len_utf8();
+                }
+                Some('/') if self.input[self.position..].starts_with("//") => {
+                    self.position = self.input[self.position..]
+                        .find('\n')
+                        .map_or(self.input.len(), |end| self.position + end);
+                }
+                _ => break,
+            }
+        }
+        self.input[start..self.position].to_string()
+    }
+
+    fn parse_inline_whitespace(&mut self) -> String {
+        let start = self.position;
+        while let Some(c @ (' ' | '\t')) = self.peek() {
+            self.position += c.len_utf8();
+        }
+        self.input[start..self.position].to_string()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        let consumed = &self.input[..self.position];
+        let line = consumed.matches('\n').count() + 1;
+        let column = consumed.len() - consumed.rfind('\n').map_or(0, |index| index + 1) + 1;
+
+        ParseError {
+            message: message.to_string(),
+            line,
+            column,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCAL_CONFIG: &str = "\"UserLocalConfigStore\"\n{\n\t\"Broadcast\"\n\t{\n\t\t\"Permissions\"\t\t\"1\"\n\t}\n\t\"Software\"\n\t{\n\t\t\"Valve\"\n\t\t{\n\t\t\t\"Steam\"\n\t\t\t{\n\t\t\t\t\"apps\"\n\t\t\t\t{\n\t\t\t\t\t\"2357570\"\n\t\t\t\t\t{\n\t\t\t\t\t\t\"LastPlayed\"\t\t\"1718000000\"\n\t\t\t\t\t\t\"Playtime\"\t\t\"1234\"\n\t\t\t\t\t\t\"LaunchOptions\"\t\t\"--lobbyMap=0x0800000000000E1F \\\"%command%\\\"\"\n\t\t\t\t\t}\n\t\t\t\t\t\"730\"\n\t\t\t\t\t{\n\t\t\t\t\t\t\"LastPlayed\"\t\t\"1700000000\"\n\t\t\t\t\t}\n\t\t\t\t}\n\t\t\t}\n\t\t}\n\t}\n\t// Written by Steam\n\t\"friends\"\n\t{\n\t\t\"PersonaName\"\t\t\"Player\"\n\t}\n}\n";
+
+    const OVERWATCH_PATH: &str = "UserLocalConfigStore/Software/Valve/Steam/apps/2357570";
+
+    #[test]
+    fn round_trips_local_config() {
+        let document = Document::parse(LOCAL_CONFIG).unwrap();
+        assert_eq!(document.to_string(), LOCAL_CONFIG);
+    }
+
+    #[test]
+    fn round_trips_bom_comments_and_unquoted_tokens() {
+        let input =
+            "\u{feff}// Header\r\nRoot\r\n{\r\n  key value // trailing\r\n  \"empty\" \"\"\r\n}";
+        let document = Document::parse(input).unwrap();
+        assert_eq!(document.to_string(), input);
+        assert_eq!(
+            document.get_object("Root").unwrap().get_str("key"),
+            Some("value")
+        );
+        assert_eq!(
+            document.get_object("Root").unwrap().get_str("empty"),
+            Some("")
+        );
+    }
+
+    #[test]
+    fn reads_escaped_quotes_and_backslashes() {
+        let input = "\"Root\"\n{\n\t\"path\"\t\t\"C:\\\\Games\\\\Overwatch\"\n\t\"args\"\t\t\"-name \\\"Tracer\\\"\"\n}\n";
+        let document = Document::parse(input).unwrap();
+        let root = document.get_object("Root").unwrap();
+
+        assert_eq!(root.get_str("path"), Some("C:\\Games\\Overwatch"));
+        assert_eq!(root.get_str("args"), Some("-name \"Tracer\""));
+        assert_eq!(document.to_string(), input);
+    }
+
+    #[test]
+    fn escapes_values_when_writing() {
+        let mut document = Document::parse("\"Root\"\n{\n\t\"args\"\t\t\"\"\n}\n").unwrap();
+        document
+            .get_object_mut("Root")
+            .unwrap()
+            .set_str("args", "-path \"C:\\Games\" %command%");
+
+        let output = document.to_string();
+        assert_eq!(
+            output,
+            "\"Root\"\n{\n\t\"args\"\t\t\"-path \\\"C:\\\\Games\\\" %command%\"\n}\n"
+        );
+        let reparsed = Document::parse(&output).unwrap();
+        assert_eq!(
+            reparsed.get_object("Root").unwrap().get_str("args"),
+            Some("-path \"C:\\Games\" %command%")
+        );
+    }
+
+    #[test]
+    fn evaluates_conditionals() {
+        let input = "\"Root\"\n{\n\t\"value\"\t\t\"windows\"\t[$WIN32]\n\t\"value\"\t\t\"other\"\t[!$WIN32]\n\t\"block\" [$WIN32||$POSIX]\n\t{\n\t\t\"key\"\t\t\"1\"\n\t}\n}\n";
+        let document = Document::parse(input).unwrap();
+        let root = document.get_object("Root").unwrap();
+
+        let expected = if cfg!(windows) { "windows" } else { "other" };
+        assert_eq!(root.get_str("value"), Some(expected));
+        assert_eq!(root.iter().filter(|(key, _)| *key == "value").count(), 1);
+        assert_eq!(root.get_str("block/key"), Some("1"));
+        assert_eq!(document.to_string(), input);
+    }
+
+    #[test]
+    fn sets_only_the_active_conditional_entry() {
+        let input = "\"Root\"\n{\n\t\"value\"\t\t\"windows\"\t[$WIN32]\n\t\"value\"\t\t\"other\"\t[!$WIN32]\n}\n";
+        let mut document = Document::parse(input).unwrap();
+        document
+            .get_object_mut("Root")
+            .unwrap()
+            .set_str("value", "new");
+
+        let expected = if cfg!(windows) {
+            "\"Root\"\n{\n\t\"value\"\t\t\"new\"\t[$WIN32]\n\t\"value\"\t\t\"other\"\t[!$WIN32]\n}\n"
+        } else {
+            "\"Root\"\n{\n\t\"value\"\t\t\"windows\"\t[$WIN32]\n\t\"value\"\t\t\"new\"\t[!$WIN32]\n}\n"
+        };
+        assert_eq!(document.to_string(), expected);
+    }
+
+    #[test]
+    fn replaces_launch_options() {
+        let mut document = Document::parse(LOCAL_CONFIG).unwrap();
+        document
+            .get_object_mut(OVERWATCH_PATH)
+            .unwrap()
+            .set_str("LaunchOptions", "--lobbyMap=0x08000000000008F3");
+
+        let expected = LOCAL_CONFIG.replace(
+            "\"--lobbyMap=0x0800000000000E1F \\\"%command%\\\"\"",
+            "\"--lobbyMap=0x08000000000008F3\"",
+        );
+        assert_eq!(document.to_string(), expected);
+    }
+
+    #[test]
+    fn keeps_unchanged_values_byte_for_byte() {
+        let input = "Root\n{\n\tLaunchOptions    unquoted\n}\n";
+        let mut document = Document::parse(input).unwrap();
+        document
+            .get_object_mut("Root")
+            .unwrap()
+            .set_str("launchoptions", "unquoted");
+
+        assert_eq!(document.to_string(), input);
+    }
+
+    #[test]
+    fn inserts_launch_options_like_its_siblings() {
+        let mut document = Document::parse(LOCAL_CONFIG).unwrap();
+        document
+            .get_object_mut("UserLocalConfigStore/Software/Valve/Steam/apps/730")
+            .unwrap()
+            .set_str("LaunchOptions", "-novid");
+
+        let expected = LOCAL_CONFIG.replace(
+            "\t\t\t\t\t\t\"LastPlayed\"\t\t\"1700000000\"\n",
+            "\t\t\t\t\t\t\"LastPlayed\"\t\t\"1700000000\"\n\t\t\t\t\t\t\"LaunchOptions\"\t\t\"-novid\"\n",
+        );
+        assert_eq!(document.to_string(), expected);
+    }
+
+    #[test]
+    fn inserts_into_an_empty_object() {
+        let mut document = Document::parse("\"Root\"\n{\n\t\"app\"\n\t{\n\t}\n}\n").unwrap();
+        document
+            .get_object_mut("Root/app")
+            .unwrap()
+            .set_str("LaunchOptions", "-novid");
+
+        assert_eq!(
+            document.to_string(),
+            "\"Root\"\n{\n\t\"app\"\n\t{\n\t\t\"LaunchOptions\"\t\t\"-novid\"\n\t}\n}\n"
+        );
+    }
+
+    #[test]
+    fn replaces_a_block_with_the_sibling_separator() {
+        let input = "Root\n{\n\tname \"Tracer\"\n\tLaunchOptions\n\t{\n\t}\n}\n";
+        let mut document = Document::parse(input).unwrap();
+        document
+            .get_object_mut("Root")
+            .unwrap()
+            .set_str("LaunchOptions", "-novid");
+
+        assert_eq!(
+            document.to_string(),
+            "Root\n{\n\tname \"Tracer\"\n\tLaunchOptions \"-novid\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn reports_unterminated_input() {
+        let error = Document::parse("\"Root\"\n{\n\t\"key\"\t\t\"value").unwrap_err();
+        assert_eq!(error.message, "Unterminated quoted string");
+        assert_eq!(error.line, 3);
+
+        assert!(Document::parse("\"Root\"\n{\n").is_err());
+        assert!(Document::parse("\"Root\" [$WIN32\n{\n}").is_err());
+    }
+}
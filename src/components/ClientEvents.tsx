@@ -0,0 +1,149 @@
+import { useQuery } from '@tanstack/react-query'
+import { listen } from '@tauri-apps/api/event'
+import { useEffect, useRef } from 'react'
+import { toast } from 'sonner'
+import { z } from 'zod'
+
+import {
+  pendingChangesQueryOptions,
+  restartOffersQueryOptions,
+  setPendingChanges,
+  setRestartOffers,
+  useCancelPendingChangeMutation,
+  useRestartClientMutation
+} from '@/lib/data'
+import { CloseProgress, LaunchChange, type Platform } from '@/lib/schemas'
+
+const platformNames: Record<Platform, string> = {
+  BattleNet: 'Battle.net',
+  Steam: 'Steam'
+}
+
+const PendingChangeFailure = z.object({ id: z.number(), error: z.string() })
+
+const showCloseProgress = (progress: CloseProgress) => {
+  const name = platformNames[progress.platform]
+  const id = `close-${progress.platform}`
+
+  switch (progress.step) {
+    case 'Requested':
+      toast.loading(`Waiting for ${name} to close...`, { id })
+      break
+    case 'WaitingForWrites':
+      toast.loading(`Waiting for ${name} to save its settings...`, { id })
+      break
+    case 'Killing':
+      toast.loading(`${name} did not close in time, forcing it to close...`, {
+        id
+      })
+      break
+    case 'Finished':
+      if (progress.result === 'Failed') {
+        toast.error(`Failed to close ${name}.`, { id })
+      } else {
+        toast.dismiss(id)
+      }
+      break
+  }
+}
+
+const describeChange = (change: LaunchChange) => {
+  switch (change.kind) {
+    case 'Background':
+      return change.value === null
+        ? 'Resetting the background'
+        : 'A new background'
+    case 'Console':
+      return change.value
+        ? 'Enabling the debug console'
+        : 'Disabling the debug console'
+    case 'LaunchOption':
+      return `A change to ${change.value.name}`
+    case 'Preset':
+      return `The ${change.value.name} preset`
+  }
+}
+
+/** Show what the backend is doing with the clients, and what waits for them. */
+export default function ClientEvents() {
+  const { data: pending } = useQuery(pendingChangesQueryOptions)
+  const { data: offers } = useQuery(restartOffersQueryOptions)
+  const { mutate: cancelChange } = useCancelPendingChangeMutation()
+  const { mutate: restartClient } = useRestartClientMutation()
+  const shownChanges = useRef<number[]>([])
+  const shownOffers = useRef<Platform[]>([])
+
+  useEffect(() => {
+    const unlisteners = [
+      listen('close_progress', (event) => {
+        const progress = CloseProgress.safeParse(event.payload)
+        if (progress.success) showCloseProgress(progress.data)
+      }),
+      listen('pending_changes', (event) => setPendingChanges(event.payload)),
+      listen('pending_change_failed', (event) => {
+        const failure = PendingChangeFailure.safeParse(event.payload)
+        if (failure.success) {
+          toast.error(`Failed to apply a pending change. ${failure.data.error}`)
+        }
+      }),
+      listen('restart_offers', (event) => setRestartOffers(event.payload))
+    ]
+
+    return () => {
+      unlisteners.forEach((unlisten) =>
+        unlisten.then((unlisten) => unlisten())
+      )
+    }
+  }, [])
+
+  useEffect(() => {
+    const ids = pending?.map((change) => change.id) ?? []
+    shownChanges.current
+      .filter((id) => !ids.includes(id))
+      .forEach((id) => toast.dismiss(`pending-${id}`))
+    shownChanges.current = ids
+
+    pending?.forEach((change) => {
+      const name = platformNames[change.targets[0]?.platform ?? 'BattleNet']
+      toast.info(
+        `${describeChange(change.change)} will be applied once ${name} closes.`,
+        {
+          id: `pending-${change.id}`,
+          duration: Infinity,
+          action: {
+            label: 'Cancel',
+            onClick: () => cancelChange(change.id)
+          }
+        }
+      )
+    })
+  }, [pending, cancelChange])
+
+  useEffect(() => {
+    const platforms = offers ?? []
+    shownOffers.current
+      .filter((platform) => !platforms.includes(platform))
+      .forEach((platform) => toast.dismiss(`restart-${platform}`))
+    shownOffers.current = platforms
+
+    platforms.forEach((platform) => {
+      toast.warning(
+        `${platformNames[platform]} was closed to apply your changes.`,
+        {
+          id: `restart-${platform}`,
+          duration: Infinity,
+          action: {
+            label: 'Restart',
+            onClick: () => restartClient({ platform, restart: true })
+          },
+          cancel: {
+            label: 'Dismiss',
+            onClick: () => restartClient({ platform, restart: false })
+          }
+        }
+      )
+    })
+  }, [offers, restartClient])
+
+  return null
+}
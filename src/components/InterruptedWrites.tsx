@@ -0,0 +1,62 @@
+import { useQuery } from '@tanstack/react-query'
+
+import {
+  interruptedWritesQueryOptions,
+  useRecoverWriteMutation
+} from '@/lib/data'
+import {
+  AlertDialog,
+  AlertDialogAction,
+  AlertDialogCancel,
+  AlertDialogContent,
+  AlertDialogDescription,
+  AlertDialogFooter,
+  AlertDialogHeader,
+  AlertDialogTitle
+} from './AlertDialog'
+
+export default function InterruptedWrites() {
+  const { data: writes } = useQuery(interruptedWritesQueryOptions)
+  const { mutate: recover, isPending } = useRecoverWriteMutation()
+  const write = writes?.[0]
+
+  return (
+    <AlertDialog open={write !== undefined}>
+      <AlertDialogContent data-ignore-global-shortcut>
+        <AlertDialogHeader>
+          <AlertDialogTitle>A change was interrupted.</AlertDialogTitle>
+          <AlertDialogDescription>
+            OverBuddy was closed while writing to{' '}
+            <span className="break-all font-medium text-zinc-300">
+              {write?.file}
+            </span>
+            .{' '}
+            {write?.journaled && write.has_temp
+              ? 'You can complete the change or restore the file to how it was before.'
+              : 'You can keep the file as it is or restore it to how it was before.'}
+          </AlertDialogDescription>
+        </AlertDialogHeader>
+        <AlertDialogFooter>
+          <AlertDialogCancel
+            disabled={isPending || !write?.has_backup}
+            onClick={() =>
+              write && recover({ file: write.file, rollForward: false })
+            }
+          >
+            Restore Original
+          </AlertDialogCancel>
+          <AlertDialogAction
+            disabled={isPending}
+            onClick={() =>
+              write && recover({ file: write.file, rollForward: true })
+            }
+          >
+            {write?.journaled && write.has_temp
+              ? 'Complete Change'
+              : 'Keep Current'}
+          </AlertDialogAction>
+        </AlertDialogFooter>
+      </AlertDialogContent>
+    </AlertDialog>
+  )
+}
@@ -1,7 +1,9 @@
+import { useQuery } from '@tanstack/react-query'
 import clsx from 'clsx'
 import { CircleXIcon, HashIcon, UserRoundIcon } from 'lucide-react'
 import { useState } from 'react'
 
+import { steamAvatarQueryOptions } from '@/lib/data'
 import type { SteamProfile } from '@/lib/schemas'
 
 export default function SteamProfileComponent({
@@ -12,6 +14,14 @@ export default function SteamProfileComponent({
   large?: boolean
 }) {
   const [isError, setIsError] = useState(false)
+  const { data: cachedAvatar, isPending } = useQuery(
+    steamAvatarQueryOptions(account.steam_id)
+  )
+  // Prefer the Steam avatar cache, which works offline
+  const avatar =
+    account.steam_id !== null && isPending
+      ? null
+      : (cachedAvatar ?? account.avatar)
 
   return (
     <div
@@ -19,9 +29,9 @@ export default function SteamProfileComponent({
       className={clsx(large ? 'flex flex-col gap-1' : 'flex shrink-0 gap-2.5')}
     >
       <div className="relative w-fit select-none">
-        {!isError && account.avatar ? (
+        {!isError && avatar ? (
           <img
-            src={account.avatar}
+            src={avatar}
             alt={`Avatar of ${account.name}`}
             onError={() => setIsError(true)}
             className={clsx(
@@ -1,10 +1,13 @@
 import { queryOptions, useMutation } from '@tanstack/react-query'
-import { invoke } from '@tauri-apps/api/core'
+import { convertFileSrc, invoke } from '@tauri-apps/api/core'
 import { check } from '@tauri-apps/plugin-updater'
 import { toast } from 'sonner'
 import { z } from 'zod'
 
+import placeholder from '@/assets/placeholder.svg'
 import {
+  BackgroundError,
+  BackgroundErrorSchema,
   ConfigError,
   ConfigErrorSchema,
   ConfigErrors,
@@ -16,9 +19,13 @@ import {
 import {
   Background,
   BackgroundArray,
+  InterruptedWrite,
   LaunchConfig,
-  SteamProfile,
-  type Platform
+  LaunchOption,
+  LaunchOptionState,
+  PendingChange,
+  Platform,
+  SteamProfile
 } from '@/lib/schemas'
 import { queryClient } from '@/main'
 import { useState } from 'react'
@@ -192,6 +199,17 @@ export const useSetupErrorMutation = ({
     onSuccess
   })
 
+export const steamAvatarQueryOptions = (steamId: string | null) =>
+  queryOptions({
+    queryKey: ['steam_avatar', steamId],
+    queryFn: async () => {
+      const data = await invoke('get_steam_avatar', { steamId })
+      return z.string().nullable().parse(JSON.parse(data as string))
+    },
+    enabled: steamId !== null,
+    staleTime: Infinity
+  })
+
 export const steamQueryOptions = queryOptions({
   queryKey: ['steam'],
   queryFn: async () => {
@@ -267,6 +285,13 @@ export const useSteamUndoMutation = ({
     onSuccess
   })
 
+/** Custom backgrounds load their image from disk, if they have one. */
+export const getBackgroundImage = (background: Background) => {
+  if (!background.custom) return `/backgrounds/${background.image}`
+  if (!background.image) return placeholder
+  return convertFileSrc(background.image)
+}
+
 export const backgroundsQueryOptions = queryOptions({
   queryKey: ['backgrounds'],
   queryFn: async () => {
@@ -284,7 +309,7 @@ export const backgroundsQueryOptions = queryOptions({
             const img = new Image()
             img.onload = () => resolve()
             img.onerror = () => reject()
-            img.src = `/backgrounds/${background.image}`
+            img.src = getBackgroundImage(background)
           })
       )
     )
@@ -328,6 +353,21 @@ export const useActiveBackgroundMutation = () =>
     }
   })
 
+export const useSeenBackgroundsMutation = () =>
+  useMutation({
+    mutationFn: async () => {
+      const data = await invoke('get_new_backgrounds')
+      const backgrounds = BackgroundArray.safeParse(JSON.parse(data as string))
+      if (!backgrounds.success) {
+        throw new Error(
+          `Failed to get new backgrounds. ${backgrounds.error.message}`
+        )
+      }
+      return backgrounds.data
+    },
+    onError: (error) => handleError(error)
+  })
+
 export const invalidateActiveBackground = () =>
   queryClient.invalidateQueries(activeBackgroundQueryOptions)
 
@@ -342,7 +382,22 @@ export const useBackgroundMutation = ({
 
   return useMutation({
     mutationFn: async (background: { id: string }) => {
-      const data = (await invoke('set_background', background)) as string
+      const data = (await invoke('set_background', background).catch(
+        (error) => {
+          if (typeof error !== 'string') throw error
+          let parsed
+          try {
+            parsed = JSON.parse(error)
+          } catch (_) {
+            throw new Error(error)
+          }
+          const backgroundError = BackgroundErrorSchema.safeParse(parsed)
+          if (backgroundError.success) {
+            throw new BackgroundError(backgroundError.data)
+          }
+          throw new Error(error)
+        }
+      )) as string
       const config = LaunchConfig.safeParse(JSON.parse(data))
       if (!config.success) {
         throw new Error(
@@ -390,6 +445,49 @@ export const useResetBackgroundMutation = ({
     onSettled
   })
 
+export const useAddCustomBackgroundMutation = () =>
+  useMutation({
+    mutationFn: async (data: {
+      id: string
+      name: string
+      image: string | null
+    }) => {
+      const query = (await invoke('add_custom_background', data)) as string
+      const config = LaunchConfig.safeParse(JSON.parse(query))
+      if (!config.success) {
+        throw new Error(
+          `Failed to add custom background. ${config.error.message}`
+        )
+      }
+      updateLaunchConfig(config.data)
+      await queryClient.invalidateQueries(backgroundsQueryOptions)
+      await queryClient.invalidateQueries(activeBackgroundQueryOptions)
+    },
+    onError: (error) => handleError(error),
+    onSuccess: () => {
+      toast.success('Successfully added the custom background.')
+    }
+  })
+
+export const useMigrateBackgroundMutation = () =>
+  useMutation({
+    mutationFn: async () => {
+      const data = (await invoke('migrate_retired_background')) as string
+      const config = LaunchConfig.safeParse(JSON.parse(data))
+      if (!config.success) {
+        throw new Error(
+          `Failed to replace retired background. ${config.error.message}`
+        )
+      }
+      updateLaunchConfig(config.data)
+      invalidateActiveBackground()
+    },
+    onError: (error) => handleError(error),
+    onSuccess: () => {
+      toast.success('Successfully replaced the retired background.')
+    }
+  })
+
 export const useDebugConsoleMutation = () =>
   useMutation({
     mutationFn: async (data: { enableConsole: boolean }) => {
@@ -420,6 +518,63 @@ export const useDebugConsoleMutation = () =>
     }
   })
 
+export const launchOptionsQueryOptions = queryOptions({
+  queryKey: ['launch_options'],
+  queryFn: async () => {
+    const data = (await invoke('get_launch_options')) as string
+    const options = z.array(LaunchOption).safeParse(JSON.parse(data))
+    if (!options.success) {
+      throw new Error(`Failed to get launch options. ${options.error.message}`)
+    }
+    return options.data
+  },
+  staleTime: Infinity
+})
+
+export const launchOptionQueryOptions = (name: string) =>
+  queryOptions({
+    queryKey: ['launch_option', name],
+    queryFn: async () => {
+      const data = (await invoke('get_launch_option', { name })) as string
+      const states = z.array(LaunchOptionState).safeParse(JSON.parse(data))
+      if (!states.success) {
+        throw new Error(
+          `Failed to get launch option ${name}. ${states.error.message}`
+        )
+      }
+      return states.data
+    }
+  })
+
+export const useLaunchOptionMutation = () =>
+  useMutation({
+    mutationFn: async (data: {
+      name: string
+      enabled: boolean
+      value: string | null
+    }) => {
+      const query = (await invoke('set_launch_option', data)) as string
+      const config = LaunchConfig.safeParse(JSON.parse(query))
+      if (!config.success) {
+        throw new Error(
+          `Failed to save launch option change. ${config.error.message}`
+        )
+      }
+      updateLaunchConfig(config.data)
+      return data
+    },
+    onError: (error) => handleError(error),
+    onSuccess: ({ name, enabled }) => {
+      queryClient.invalidateQueries({ queryKey: ['launch_option', name] })
+      toast.success(
+        enabled
+          ? `The ${name} launch option has been set.`
+          : `The ${name} launch option has been removed.`,
+        { id: `launch-option-${name}` }
+      )
+    }
+  })
+
 export const useResetMutation = ({
   onSuccess,
   onError,
@@ -450,6 +605,107 @@ export const useResetMutation = ({
     onSettled
   })
 
+const pendingChangesQueryKey = ['pending_changes']
+
+export const setPendingChanges = (data: unknown) => {
+  const pending = z.array(PendingChange).safeParse(data)
+  if (!pending.success) {
+    throw new Error(`Failed to get pending changes. ${pending.error.message}`)
+  }
+  queryClient.setQueryData(pendingChangesQueryKey, pending.data)
+  return pending.data
+}
+
+export const pendingChangesQueryOptions = queryOptions({
+  queryKey: pendingChangesQueryKey,
+  queryFn: async () => {
+    const data = (await invoke('get_pending_changes')) as string
+    return setPendingChanges(JSON.parse(data))
+  },
+  staleTime: Infinity
+})
+
+export const useCancelPendingChangeMutation = () =>
+  useMutation({
+    mutationFn: async (id: number) => {
+      const data = (await invoke('cancel_pending_change', { id })) as string
+      setPendingChanges(JSON.parse(data))
+    },
+    onError: (error) => handleError(error),
+    onSuccess: () => {
+      toast.success('The pending change has been cancelled.')
+    }
+  })
+
+const restartOffersQueryKey = ['restart_offers']
+
+export const setRestartOffers = (data: unknown) => {
+  const offers = z.array(Platform).safeParse(data)
+  if (!offers.success) {
+    throw new Error(`Failed to get restart offers. ${offers.error.message}`)
+  }
+  queryClient.setQueryData(restartOffersQueryKey, offers.data)
+  return offers.data
+}
+
+export const restartOffersQueryOptions = queryOptions({
+  queryKey: restartOffersQueryKey,
+  queryFn: async () => {
+    const data = (await invoke('get_restart_offers')) as string
+    return setRestartOffers(JSON.parse(data))
+  },
+  staleTime: Infinity
+})
+
+export const useRestartClientMutation = () =>
+  useMutation({
+    mutationFn: async (data: { platform: Platform; restart: boolean }) => {
+      const offers = (await invoke('restart_client', data)) as string
+      setRestartOffers(JSON.parse(offers))
+    },
+    onError: (error) => handleError(error)
+  })
+
+const interruptedWritesQueryKey = ['interrupted_writes']
+
+const parseInterruptedWrites = (data: string) => {
+  const writes = z.array(InterruptedWrite).safeParse(JSON.parse(data))
+  if (!writes.success) {
+    throw new Error(`Failed to get interrupted writes. ${writes.error.message}`)
+  }
+  return writes.data
+}
+
+export const interruptedWritesQueryOptions = queryOptions({
+  queryKey: interruptedWritesQueryKey,
+  queryFn: async () => {
+    const data = (await invoke('get_interrupted_writes')) as string
+    return parseInterruptedWrites(data)
+  },
+  staleTime: Infinity
+})
+
+export const useRecoverWriteMutation = () =>
+  useMutation({
+    mutationFn: async (data: { file: string; rollForward: boolean }) => {
+      const writes = parseInterruptedWrites(
+        (await invoke('recover_interrupted_write', data)) as string
+      )
+      queryClient.setQueryData(interruptedWritesQueryKey, writes)
+      return data.rollForward
+    },
+    onError: (error) => handleError(error),
+    onSuccess: (rollForward) => {
+      queryClient.invalidateQueries(launchQueryOptions)
+      invalidateActiveBackground()
+      toast.success(
+        rollForward
+          ? 'The interrupted change has been completed.'
+          : 'The interrupted change has been rolled back.'
+      )
+    }
+  })
+
 type useCheckUpdatesReturnType =
   | { available: false }
   | {
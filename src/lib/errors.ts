@@ -35,6 +35,26 @@ export class ConfigError extends Error {
   }
 }
 
+/* BackgroundError Response Schema */
+export const BackgroundErrorSchema = z.object({
+  error_key: z.enum(['MalformedId', 'UnknownId', 'Retired']),
+  message: z.string(),
+  id: z.string()
+})
+export type BackgroundErrorSchema = z.infer<typeof BackgroundErrorSchema>
+
+/* BackgroundError Class */
+export class BackgroundError extends Error {
+  error_key: BackgroundErrorSchema['error_key']
+  id: BackgroundErrorSchema['id']
+
+  constructor(public error: BackgroundErrorSchema) {
+    super(error.message)
+    this.error_key = error.error_key
+    this.id = error.id
+  }
+}
+
 /* SetupError Class */
 export class SetupError extends Error {
   constructor() {
@@ -44,7 +64,8 @@ export class SetupError extends Error {
 
 export const SetupPathResponse = z.object({
   path: z.string().nullable(),
-  defaultPath: z.string().nullable()
+  defaultPath: z.string().nullable(),
+  file: z.string().optional()
 })
 export type SetupPathResponse = z.infer<typeof SetupPathResponse>
 
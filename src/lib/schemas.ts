@@ -10,18 +10,167 @@ const SteamLocalconfig = z.object({
 
 export const SteamProfile = z.object({
   id: z.string(),
+  steam_id: z.string().nullable(),
   name: z.string(),
+  account_name: z.string().nullable(),
   avatar: z.string().url().nullable(),
-  has_overwatch: z.boolean()
+  has_overwatch: z.boolean(),
+  enabled: z.boolean()
 })
 export type SteamProfile = z.infer<typeof SteamProfile>
 
+const SteamOverwatchInstall = z.object({
+  library: z.string(),
+  install_dir: z.string(),
+  build_id: z.string().nullable(),
+  state: z.enum(['Installed', 'UpdateRequired', 'Updating', 'Incomplete'])
+})
+
+export const Target = z.discriminatedUnion('platform', [
+  z.object({ platform: z.literal('BattleNet') }),
+  z.object({ platform: z.literal('Steam'), account: z.string() })
+])
+export type Target = z.infer<typeof Target>
+
+export const RetiredBackground = z.object({
+  id: z.string(),
+  name: z.string(),
+  reason: z.string(),
+  successor: z.string().nullable()
+})
+export type RetiredBackground = z.infer<typeof RetiredBackground>
+
+export const TargetState = z.object({
+  target: Target,
+  background: z.object({
+    current: z.string().nullable(),
+    is_outdated: z.boolean(),
+    unknown: z.string().nullable(),
+    retired: RetiredBackground.nullable()
+  }),
+  additional: z.object({
+    console_enabled: z.boolean()
+  })
+})
+export type TargetState = z.infer<typeof TargetState>
+
+export const CustomBackground = z.object({
+  id: z.string(),
+  name: z.string(),
+  image: z.string().nullable()
+})
+export type CustomBackground = z.infer<typeof CustomBackground>
+
+export const PresetOption = z.object({
+  name: z.string(),
+  enabled: z.boolean(),
+  value: z.string().nullable()
+})
+export type PresetOption = z.infer<typeof PresetOption>
+
+export const Preset = z.object({
+  name: z.string(),
+  background: z.string().nullable(),
+  console_enabled: z.boolean(),
+  options: z.array(PresetOption)
+})
+export type Preset = z.infer<typeof Preset>
+
+export const BackgroundTag = z.enum([
+  'Animated',
+  'Animated Art',
+  'Art',
+  'Collaboration',
+  'Mythic Weapon',
+  'Overwatch League',
+  'World Cup',
+  'Overwatch Champions Series',
+  'Charity',
+  'No Hero',
+  'Crowd Sounds',
+  'Overwatch 1.0 Patch'
+])
+export type BackgroundTag = z.infer<typeof BackgroundTag>
+
+export const RotationPool = z.discriminatedUnion('kind', [
+  z.object({ kind: z.literal('Catalog') }),
+  z.object({ kind: z.literal('Tags'), value: z.array(BackgroundTag) }),
+  z.object({ kind: z.literal('Backgrounds'), value: z.array(z.string()) })
+])
+export type RotationPool = z.infer<typeof RotationPool>
+
+export const RotationTrigger = z.enum(['Startup', 'GameExit'])
+export type RotationTrigger = z.infer<typeof RotationTrigger>
+
+export const RotationConfig = z.object({
+  pool: RotationPool,
+  trigger: RotationTrigger,
+  used: z.array(z.string())
+})
+export type RotationConfig = z.infer<typeof RotationConfig>
+
+export const Collection = z.object({
+  name: z.string(),
+  backgrounds: z.array(z.string())
+})
+export type Collection = z.infer<typeof Collection>
+
+export const LaunchChange = z.discriminatedUnion('kind', [
+  z.object({ kind: z.literal('Background'), value: z.string().nullable() }),
+  z.object({ kind: z.literal('Console'), value: z.boolean() }),
+  z.object({
+    kind: z.literal('LaunchOption'),
+    value: z.object({
+      name: z.string(),
+      enabled: z.boolean(),
+      value: z.string().nullable()
+    })
+  }),
+  z.object({ kind: z.literal('Preset'), value: Preset })
+])
+export type LaunchChange = z.infer<typeof LaunchChange>
+
+export const PendingChange = z.object({
+  id: z.number(),
+  targets: z.array(Target),
+  change: LaunchChange
+})
+export type PendingChange = z.infer<typeof PendingChange>
+
+export const CloseResult = z.enum(['NotRunning', 'Requested', 'Killed', 'Failed'])
+export type CloseResult = z.infer<typeof CloseResult>
+
+export const CloseProgress = z.discriminatedUnion('step', [
+  z.object({ platform: Platform, step: z.literal('Requested') }),
+  z.object({ platform: Platform, step: z.literal('WaitingForWrites') }),
+  z.object({ platform: Platform, step: z.literal('Killing') }),
+  z.object({
+    platform: Platform,
+    step: z.literal('Finished'),
+    result: CloseResult
+  })
+])
+export type CloseProgress = z.infer<typeof CloseProgress>
+
+export const RestartPolicy = z.enum(['Never', 'Ask', 'Always'])
+export type RestartPolicy = z.infer<typeof RestartPolicy>
+
+export const WinePrefix = z.object({
+  path: z.string(),
+  config: z.string(),
+  install: z.string().nullable(),
+  runner: z.string().nullable().default(null)
+})
+export type WinePrefix = z.infer<typeof WinePrefix>
+
 export const LaunchConfig = z.object({
   is_setup: z.boolean(),
   battle_net: z.object({
     enabled: z.boolean(),
     config: z.string().nullable(),
-    install: z.string().nullable()
+    install: z.string().nullable(),
+    prefixes: z.array(WinePrefix),
+    restart: RestartPolicy
   }),
   steam: z.object({
     enabled: z.boolean(),
@@ -29,35 +178,115 @@ export const LaunchConfig = z.object({
     advertised: z.number(),
     install: z.string().nullable(),
     configs: z.array(SteamLocalconfig).nullable(),
-    profiles: z.array(SteamProfile).nullable()
+    profiles: z.array(SteamProfile).nullable(),
+    overwatch: SteamOverwatchInstall.nullable(),
+    restart: RestartPolicy
   }),
   shared: z.object({
     background: z.object({
       current: z.string().nullable(),
-      is_outdated: z.boolean()
+      is_outdated: z.boolean(),
+      unknown: z.string().nullable(),
+      retired: RetiredBackground.nullable()
     }),
     additional: z.object({
       console_enabled: z.boolean()
     })
-  })
+  }),
+  targets: z.array(TargetState),
+  defer_changes: z.boolean(),
+  pending: z.array(PendingChange),
+  presets: z.array(Preset),
+  custom_backgrounds: z.array(CustomBackground),
+  seen_backgrounds: z.array(z.string()).nullable(),
+  favourites: z.array(z.string()),
+  collections: z.array(Collection),
+  rotation: RotationConfig.nullable()
 })
 export type LaunchConfig = z.infer<typeof LaunchConfig>
 
+export const InterruptedWrite = z.object({
+  file: z.string(),
+  journaled: z.boolean(),
+  has_backup: z.boolean(),
+  has_temp: z.boolean()
+})
+export type InterruptedWrite = z.infer<typeof InterruptedWrite>
+
+export const BackgroundStatus = z.discriminatedUnion('kind', [
+  z.object({ kind: z.literal('Active') }),
+  z.object({ kind: z.literal('Broken'), since: z.string().nullable() }),
+  z.object({ kind: z.literal('Removed'), since: z.string().nullable() })
+])
+export type BackgroundStatus = z.infer<typeof BackgroundStatus>
+
 export const Background = z.object({
   id: z.string(),
   image: z.string(),
   name: z.string(),
   description: z.string(),
-  tags: z.array(z.string()),
-  new: z.boolean()
+  season: z.number().nullable(),
+  release_date: z.string().nullable(),
+  event: z.string().nullable(),
+  heroes: z.array(z.string()),
+  tags: z.array(BackgroundTag),
+  has_music: z.boolean().nullable(),
+  music_credit: z.string().nullable(),
+  status: BackgroundStatus,
+  successor: z.string().nullable(),
+  new: z.boolean(),
+  custom: z.boolean()
 })
 export type Background = z.infer<typeof Background>
 
 export const BackgroundArray = z.array(Background)
 export type BackgroundArray = z.infer<typeof BackgroundArray>
 
+export const SavedBackground = z.object({
+  id: z.string(),
+  background: Background.nullable()
+})
+export type SavedBackground = z.infer<typeof SavedBackground>
+
+export const ListedCollection = z.object({
+  name: z.string(),
+  backgrounds: z.array(SavedBackground)
+})
+export type ListedCollection = z.infer<typeof ListedCollection>
+
+export const BackgroundFilter = z.object({
+  season: z.number().optional(),
+  event: z.string().optional(),
+  hero: z.string().optional(),
+  tags: z.array(BackgroundTag).optional(),
+  has_music: z.boolean().optional(),
+  include_retired: z.boolean().optional()
+})
+export type BackgroundFilter = z.infer<typeof BackgroundFilter>
+
 export const SettingsData = z.object({
   platforms: z.array(Platform),
   steam_profiles: z.array(SteamProfile).nullable()
 })
 export type SettingsData = z.infer<typeof SettingsData>
+
+export const OptionKind = z.discriminatedUnion('type', [
+  z.object({ type: z.literal('Flag') }),
+  z.object({ type: z.literal('Integer'), min: z.number(), max: z.number() }),
+  z.object({ type: z.literal('HexId') })
+])
+export type OptionKind = z.infer<typeof OptionKind>
+
+export const LaunchOption = z.object({
+  name: z.string(),
+  kind: OptionKind,
+  description: z.string()
+})
+export type LaunchOption = z.infer<typeof LaunchOption>
+
+export const LaunchOptionState = z.object({
+  target: Target,
+  enabled: z.boolean(),
+  value: z.string().nullable()
+})
+export type LaunchOptionState = z.infer<typeof LaunchOptionState>
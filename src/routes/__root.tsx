@@ -1,6 +1,9 @@
 import { QueryClient } from '@tanstack/react-query'
 import { Outlet, createRootRouteWithContext } from '@tanstack/react-router'
 
+import ClientEvents from '@/components/ClientEvents'
+import InterruptedWrites from '@/components/InterruptedWrites'
+
 export const Route = createRootRouteWithContext<{
   queryClient: QueryClient
 }>()({
@@ -11,6 +14,8 @@ function RootComponent() {
   return (
     <div className="h-screen min-h-screen">
       <Outlet />
+      <InterruptedWrites />
+      <ClientEvents />
     </div>
   )
 }
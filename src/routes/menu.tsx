@@ -25,11 +25,15 @@ import {
   backgroundsQueryOptions,
   launchQueryOptions,
   shouldAdvertiseQueryOptions,
+  getBackgroundImage,
   updateQueryOptions,
   useActiveBackgroundMutation,
+  useAddCustomBackgroundMutation,
   useBackgroundMutation,
   useDismissAdMutation,
-  useResetBackgroundMutation
+  useMigrateBackgroundMutation,
+  useResetBackgroundMutation,
+  useSeenBackgroundsMutation
 } from '@/lib/data'
 import { linkFix } from '@/lib/linkFix'
 import useKeyPress from '@/lib/useKeyPress'
@@ -113,6 +117,9 @@ function Menu() {
   const backgroundRefs = useRef<HTMLButtonElement[]>([])
   const { mutate: setActiveBackground } = useActiveBackgroundMutation()
   const { mutate: dismissAd } = useDismissAdMutation()
+  const { mutate: markBackgroundsSeen } = useSeenBackgroundsMutation()
+  const { mutate: addCustomBackground } = useAddCustomBackgroundMutation()
+  const { mutate: migrateBackground } = useMigrateBackgroundMutation()
 
   const [newBackground] = useState(
     backgrounds.findIndex((bg) => bg.new) || null
@@ -266,6 +273,59 @@ function Menu() {
       toast.dismiss('outdated-background')
     }
   }, [config.shared.background.is_outdated, resetBackground])
+  // Retired background toast
+  const retired = [config.shared, ...config.targets].find(
+    (state) => state.background.retired
+  )?.background.retired
+  const successor = backgrounds.find((bg) => bg.id === retired?.successor)
+  useEffect(() => {
+    if (retired) {
+      toast.warning(
+        `${retired.reason.replaceAll(/\[\[|\]\]/g, '"')}.` +
+          (successor ? ` It is replaced by "${successor.name}".` : ''),
+        {
+          id: 'retired-background',
+          action: successor
+            ? {
+                label: 'Migrate',
+                onClick: () => migrateBackground()
+              }
+            : undefined,
+          duration: Infinity
+        }
+      )
+    }
+
+    return () => {
+      toast.dismiss('retired-background')
+    }
+  }, [retired, successor, migrateBackground])
+  // Unknown background toast
+  useEffect(() => {
+    const unknown = config.shared.background.unknown
+    if (unknown) {
+      toast.info(
+        `Your game uses a background OverBuddy does not know (${unknown}).`,
+        {
+          id: 'unknown-background',
+          action: {
+            label: 'Import',
+            onClick: () =>
+              addCustomBackground({
+                id: unknown,
+                name: `Imported ${unknown}`,
+                image: null
+              })
+          },
+          duration: Infinity
+        }
+      )
+    }
+
+    return () => {
+      toast.dismiss('unknown-background')
+    }
+  }, [config.shared.background.unknown, addCustomBackground])
   // Advertise Steam feature
   useEffect(() => {
     if (shouldAdvertise && config.steam.advertised < 4) {
@@ -312,6 +372,7 @@ function Menu() {
   const handleSelect = (index: number) => {
     if (newBackground && index === newBackground) {
       setShowNewButton(false)
+      markBackgroundsSeen()
     }
 
     const ref = backgroundRefs.current[index]
@@ -391,7 +452,7 @@ function Menu() {
                     ? 'rounded-xl'
                     : 'rounded-lg'
                 )}
-                src={`/backgrounds/${background.image}`}
+                src={getBackgroundImage(background)}
                 onError={onImageError}
                 aria-hidden
               />
@@ -498,7 +559,13 @@ function Menu() {
               className="scrollbar-hide flex h-fit w-fit flex-wrap gap-2 text-sm"
               key={activeBackground.id}
             >
-              {activeBackground.tags.map((tag) => (
+              {[
+                ...activeBackground.tags,
+                ...(activeBackground.has_music === false ? ['No Music'] : []),
+                ...(activeBackground.music_credit
+                  ? [`Song: ${activeBackground.music_credit}`]
+                  : [])
+              ].map((tag) => (
                 <motion.p
                   key={tag}
                   className="flex-shrink-0 rounded-md border border-zinc-800/80 bg-zinc-700/80 px-2 py-1 font-medium text-zinc-100 backdrop-blur"
@@ -677,7 +744,7 @@ function Menu() {
             <img
               alt={`${activeBackground.name} Background`}
               className="pointer-events-none h-full w-full select-none rounded-lg object-cover shadow-lg"
-              src={`/backgrounds/${activeBackground.image}`}
+              src={getBackgroundImage(activeBackground)}
               onError={onImageError}
               draggable={false}
             />
@@ -39,17 +39,20 @@ import {
 } from '@/lib/animations'
 import {
   invalidateActiveBackground,
+  launchOptionQueryOptions,
+  launchOptionsQueryOptions,
   launchQueryOptions,
   updateQueryOptions,
   useCheckUpdates,
   useDebugConsoleMutation,
+  useLaunchOptionMutation,
   useResetMutation,
   useSetupMutation,
   useUpdateMutation
 } from '@/lib/data'
 import { ConfigError, ConfigErrors, SetupError } from '@/lib/errors'
 import preventReload from '@/lib/preventReload'
-import type { Platform } from '@/lib/schemas'
+import type { LaunchOption, Platform } from '@/lib/schemas'
 import useKeyPress from '@/lib/useKeyPress'
 
 export const Route = createFileRoute('/settings')({
@@ -229,6 +232,7 @@ function Settings() {
               <p className="select-none">Advanced tools.</p>
             </div>
             <ToggleConsole />
+            <LaunchOptions />
             {/* TODO: Set custom background id (full and truncated) */}
           </motion.div>
           <motion.div
@@ -894,3 +898,71 @@ function ToggleConsole() {
     </div>
   )
 }
+
+/** Launch options without a dedicated setting, such as the background. */
+const dedicatedOptions = ['--lobbyMap', '--tank_Console']
+
+function LaunchOptions() {
+  const { data: options } = useQuery(launchOptionsQueryOptions)
+
+  return options
+    ?.filter((option) => !dedicatedOptions.includes(option.name))
+    .map((option) => (
+      <LaunchOptionSetting key={option.name} option={option} />
+    ))
+}
+
+function LaunchOptionSetting({ option }: { option: LaunchOption }) {
+  const { data: states } = useQuery(launchOptionQueryOptions(option.name))
+  const { mutate, status } = useLaunchOptionMutation()
+  const [value, setValue] = useState('')
+
+  // Only report the option as set when every target agrees
+  const enabled =
+    !!states && states.length > 0 && states.every((state) => state.enabled)
+  const currentValue = enabled ? (states[0]?.value ?? null) : null
+  useEffect(() => {
+    if (currentValue !== null) setValue(currentValue)
+  }, [currentValue])
+
+  const takesValue = option.kind.type !== 'Flag'
+  const isChanged = takesValue && value !== (currentValue ?? '')
+
+  return (
+    <div className="flex w-full items-center gap-4">
+      <MotionButton
+        className="w-fit min-w-[12.5625rem] disabled:pointer-events-none disabled:!opacity-100"
+        onClick={() =>
+          mutate({
+            name: option.name,
+            enabled: !enabled || isChanged,
+            value: takesValue ? value : null
+          })
+        }
+        disabled={status === 'pending' || (takesValue && value === '')}
+      >
+        {status === 'pending' ? (
+          <LoaderPinwheel className="mx-auto animate-spin" />
+        ) : enabled && !isChanged ? (
+          `Remove ${option.name}`
+        ) : (
+          `Set ${option.name}`
+        )}
+      </MotionButton>
+      {takesValue && (
+        <input
+          className="w-32 rounded-lg bg-zinc-800 px-3 py-2 text-white outline-none focus-visible:ring-2 focus-visible:ring-zinc-600"
+          type={option.kind.type === 'Integer' ? 'number' : 'text'}
+          min={option.kind.type === 'Integer' ? option.kind.min : undefined}
+          max={option.kind.type === 'Integer' ? option.kind.max : undefined}
+          placeholder={option.kind.type === 'HexId' ? '0x...' : undefined}
+          value={value}
+          onChange={(event) => setValue(event.target.value)}
+          // Typing must not trigger the global shortcuts
+          onKeyDown={(event) => event.stopPropagation()}
+        />
+      )}
+      <p className="mt-1 select-none text-zinc-400">{option.description}</p>
+    </div>
+  )
+}
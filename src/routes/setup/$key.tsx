@@ -38,7 +38,7 @@ function ConfigureComponent() {
   const { key } = Route.useParams() as { key: ConfigErrors }
   const { message, platforms, redirect } = Route.useSearch()
   const {
-    data: { path, defaultPath }
+    data: { path, defaultPath, file: steamFile = 'steam.exe' }
   } = useSuspenseQuery(getSetupPath(key))
   const {
     data: { is_setup }
@@ -117,7 +117,7 @@ function ConfigureComponent() {
             ) : key === 'SteamInstall' ? (
               <>
                 If you have Steam installed, please select the{' '}
-                <Highlight>steam.exe</Highlight> file, which is located in your
+                <Highlight>{steamFile}</Highlight> file, which is located in your
                 Steam installation directory (defaults to{' '}
                 <Highlight>{path}</Highlight>).
               </>
@@ -126,7 +126,7 @@ function ConfigureComponent() {
                 <>
                   Please ensure you have logged into an account on Steam. If you
                   have already done so, please select the correct{' '}
-                  <Highlight>steam.exe</Highlight> file, which is located in
+                  <Highlight>{steamFile}</Highlight> file, which is located in
                   your Steam installation directory (defaults to{' '}
                   <Highlight>{path}</Highlight>).
                 </>
@@ -186,7 +186,13 @@ function ConfigureComponent() {
                         : key === 'BattleNetConfig'
                           ? 'Configuration File'
                           : 'steam',
-                    extensions: [key.endsWith('Config') ? 'config' : 'exe']
+                    extensions: [
+                      key.endsWith('Config')
+                        ? 'config'
+                        : key.startsWith('Steam')
+                          ? (steamFile.split('.').pop() ?? 'exe')
+                          : 'exe'
+                    ]
                   }
                 ],
                 defaultPath: defaultPath || undefined
@@ -197,7 +203,7 @@ function ConfigureComponent() {
                   ? 'Battle.net Launcher.exe'
                   : key === 'BattleNetConfig'
                     ? 'Battle.net.config'
-                    : 'steam.exe'
+                    : steamFile
               if (selected.indexOf(file) === -1) {
                 toast.error(`Please select the "${file}" file.`, {
                   closeButton: false
@@ -216,7 +222,7 @@ function ConfigureComponent() {
               ? 'Battle.net Launcher.exe'
               : key === 'BattleNetConfig'
                 ? 'Battle.net.config'
-                : 'steam.exe'}
+                : steamFile}
           </Button>
           {/* TODO: report issue */}
         </>